@@ -7,6 +7,28 @@ declare_id!("8nuTp2x4c8bF668xLkg51TncSYPGcnyWMQczH8AmVfwJ");
 pub const NO_TOKEN_BURN_SEED: &[u8] = b"no_token_burn";
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+/// Token account holding collateral escrowed by resting bids
+pub const ORDER_VAULT_SEED: &[u8] = b"order_vault";
+/// Pending optimistic-oracle proposal for a market or answer
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+/// Token account holding proposer and disputer bonds
+pub const BOND_VAULT_SEED: &[u8] = b"bond_vault";
+/// Challenge window for new markets (2 hours)
+pub const DEFAULT_CHALLENGE_WINDOW: i64 = 2 * 60 * 60;
+/// Time after `resolution_time` when only the market's resolver may propose (24 hours)
+pub const RESOLVER_EXCLUSIVE_WINDOW: i64 = 24 * 60 * 60;
+/// Time a disputed proposal has to reach a ruling before it lapses to N/A (7 days)
+pub const DISPUTE_VOTING_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Time after `resolution_time` before anyone can cancel an unresolved market (30 days)
+pub const DEFAULT_RESOLUTION_GRACE: i64 = 30 * 24 * 60 * 60;
+/// Committee arbitrating disputes: global at `[COMMITTEE_SEED]`, per market at `[COMMITTEE_SEED, market]`
+pub const COMMITTEE_SEED: &[u8] = b"committee";
+pub const MAX_COMMITTEE_MEMBERS: usize = 10;
+/// Fixed-point scale for LMSR math (1.0 = 10^18)
+pub const WAD: i128 = 1_000_000_000_000_000_000;
+pub const LN_2_WAD: i128 = 693_147_180_559_945_309;
+/// Largest LMSR liquidity parameter; keeps b·ln(x) inside i128
+pub const MAX_LMSR_B: u64 = 1_000_000_000_000_000;
 
 #[program]
 pub mod likeli_contracts {
@@ -31,15 +53,36 @@ pub mod likeli_contracts {
         require!(initial_liquidity >= 100, LikeliError::InsufficientLiquidity);
 
         market.creator = ctx.accounts.creator.key();
+        market.resolver = ctx.accounts.creator.key();
         market.question = question;
         market.resolution_time = resolution_time;
+        market.close_time = resolution_time;
         market.yes_pool = initial_liquidity;
         market.no_pool = initial_liquidity;
+        // The creator's seed liquidity is credited as LP shares by initialize_market_vault
+        market.total_lp_shares = initial_liquidity;
         market.total_volume = 0;
         market.resolved = false;
-        market.outcome = false;
+        market.outcome = None;
+        market.resolution_bps = 0;
         market.created_at = clock.unix_timestamp;
         market.bump = ctx.bumps.market;
+        // Bound later by create_orderbook
+        market.orderbook = Pubkey::default();
+        market.tick_size = 1;
+        market.min_order_qty = 1;
+        market.oracle_bond = 0;
+        market.challenge_window = DEFAULT_CHALLENGE_WINDOW;
+        market.committee = Pubkey::default();
+        market.resolution_grace = DEFAULT_RESOLUTION_GRACE;
+        market.abandoned = false;
+        market.refund_basis = 0;
+        market.proposal_pending = false;
+        market.price_feed = Pubkey::default();
+        market.price_feed_owner = Pubkey::default();
+        market.strike_price = 0;
+        market.strike_expo = 0;
+        market.max_staleness = 0;
         
         // Multi-choice support (for legacy binary that belongs to a group)
         market.group_id = group_id;
@@ -56,16 +99,61 @@ pub mod likeli_contracts {
         Ok(())
     }
 
-    /// Initialize orderbook for a market
+    /// Create the orderbook for a binary market. Creator only.
+    /// The client allocates the account at `Orderbook::SPACE` bytes; it is bound
+    /// to the market here and required for trading from then on
     pub fn create_orderbook(ctx: Context<CreateOrderbook>) -> Result<()> {
-        let orderbook = &mut ctx.accounts.orderbook;
-        orderbook.market = ctx.accounts.market.key();
-        orderbook.yes_buy_orders = Vec::new();
-        orderbook.yes_sell_orders = Vec::new();
-        orderbook.no_buy_orders = Vec::new();
-        orderbook.no_sell_orders = Vec::new();
+        let market = &mut ctx.accounts.market;
+        require_keys_eq!(market.orderbook, Pubkey::default(), LikeliError::OrderbookExists);
+
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.market = market.key();
+        market.orderbook = ctx.accounts.orderbook.key();
         
-        msg!("Orderbook created for market: {}", orderbook.market);
+        msg!("Orderbook {} created for market: {}", market.orderbook, orderbook.market);
+        Ok(())
+    }
+
+    /// Initialize the collateral vault for a binary market
+    /// Must be called by the creator before any trading; seeds the vault with
+    /// the initial liquidity backing the CPMM pools
+    pub fn initialize_market_vault(ctx: Context<InitializeMarketVault>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        // Pools hold complete sets, so the larger side is what must be collateralized
+        let seed_liquidity = market.yes_pool.max(market.no_pool);
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.creator_ata.to_account_info(),
+            to: ctx.accounts.vault_ata.to_account_info(),
+            authority: ctx.accounts.creator.to_account_info(),
+        };
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+            ),
+            seed_liquidity,
+        )?;
+
+        let lp_position = &mut ctx.accounts.lp_position;
+        lp_position.owner = ctx.accounts.creator.key();
+        lp_position.market = market.key();
+        lp_position.shares = market.total_lp_shares;
+        lp_position.bump = ctx.bumps.lp_position;
+
+        // The seed is an LP deposit, refunded like any other if the market resolves N/A
+        let user_position = &mut ctx.accounts.user_position;
+        user_position.owner = ctx.accounts.creator.key();
+        user_position.market = market.key();
+        user_position.add_cost_basis(seed_liquidity, &mut market.refund_basis);
+
+        msg!(
+            "Vault initialized for market {} with {} liquidity. Vault authority: {}",
+            market.key(),
+            seed_liquidity,
+            ctx.accounts.vault_authority.key()
+        );
         Ok(())
     }
 
@@ -78,10 +166,9 @@ pub mod likeli_contracts {
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let user_position = &mut ctx.accounts.user_position;
-        let orderbook = &ctx.accounts.orderbook;
-        let remaining_accounts = ctx.remaining_accounts;
         
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(amount > 0, LikeliError::InvalidAmount);
 
         // Calculate fees
@@ -97,40 +184,25 @@ pub mod likeli_contracts {
         };
 
         let match_result = try_match_against_orderbook(
-            orderbook, 
-            remaining_accounts, 
-            None,
+            ctx.accounts.orderbook.as_ref(),
+            market.orderbook,
             outcome, 
             true, // is_buy
             cpmm_price, 
-            amount_after_fee
+            amount_after_fee,
+            ctx.accounts.buyer.key(),
+            market.min_order_qty,
         )?;
 
-        let mut total_shares = 0;
-        
-        // Handle matched portion (Direct swaps would go here, simplified for now: matches act as liquidity)
-        if match_result.filled_amount > 0 {
-            // For now, we simulate matching by giving shares at the matched price
-            // In a full implementation, we'd transfer from limit order owners
-            let matched_shares = (match_result.filled_amount as u128 * 10000 / cpmm_price.max(1) as u128) as u64;
-            total_shares += matched_shares;
-        }
+        // 1. Shares bought from resting asks at the makers' prices
+        let mut total_shares = match_result.filled_amount;
 
         // 2. CPMM for the remainder
         if match_result.remaining_amount > 0 {
-            let shares = if outcome {
-                calculate_shares_out(market.yes_pool, market.no_pool, match_result.remaining_amount, true)
-            } else {
-                calculate_shares_out(market.yes_pool, market.no_pool, match_result.remaining_amount, false)
-            };
-            
-            if outcome {
-                // Buy YES: add to NO pool to increase price
-                market.no_pool = market.no_pool.checked_add(match_result.remaining_amount).unwrap();
-            } else {
-                // Buy NO: add to YES pool to increase price
-                market.yes_pool = market.yes_pool.checked_add(match_result.remaining_amount).unwrap();
-            }
+            let (shares, yes_pool, no_pool) =
+                cpmm_buy(market.yes_pool, market.no_pool, match_result.remaining_amount, outcome);
+            market.yes_pool = yes_pool;
+            market.no_pool = no_pool;
             total_shares += shares;
         }
 
@@ -145,8 +217,36 @@ pub mod likeli_contracts {
 
         user_position.owner = ctx.accounts.buyer.key();
         user_position.market = market.key();
+        user_position.add_cost_basis(amount, &mut market.refund_basis);
         market.total_volume = market.total_volume.checked_add(amount).unwrap();
 
+        // Collateral matched against asks goes to the order vault for makers to
+        // settle; the rest (minted sets, pool trade, fee) goes to the market vault
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.buyer,
+            match_result.direct_notional(),
+        )?;
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.buyer,
+            amount - match_result.direct_notional(),
+        )?;
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            &match_result,
+            true,
+        )?;
+
         msg!(
             "Bought {} shares ({} matched) of {} for {} (min: {})",
             total_shares,
@@ -168,9 +268,9 @@ pub mod likeli_contracts {
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let user_position = &mut ctx.accounts.user_position;
-        let orderbook = &ctx.accounts.orderbook;
         
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(shares_to_sell > 0, LikeliError::InvalidAmount);
 
         // Verify user has enough shares
@@ -189,41 +289,26 @@ pub mod likeli_contracts {
         };
 
         let match_result = try_match_against_orderbook(
-            orderbook, 
-            ctx.remaining_accounts, 
-            None,
+            ctx.accounts.orderbook.as_ref(),
+            market.orderbook,
             outcome, 
             false, // is_buy = false (Selling)
             cpmm_price, 
-            shares_to_sell
+            shares_to_sell,
+            ctx.accounts.buyer.key(),
+            market.min_order_qty,
         )?;
 
         let mut total_payout = 0;
 
-        if match_result.filled_amount > 0 {
-            let matched_payout = (match_result.filled_amount as u128 * cpmm_price as u128 / 10000) as u64;
-            total_payout += matched_payout;
-        }
+        // Collateral from resting bids at the makers' prices
+        total_payout += match_result.notional;
 
         if match_result.remaining_amount > 0 {
-            // Sell YES for collateral: payout = shares * no_pool / (yes_pool + shares)
-            let payout = if outcome {
-                (match_result.remaining_amount as u128)
-                    .checked_mul(market.no_pool as u128).unwrap()
-                    .checked_div((market.yes_pool as u128).checked_add(match_result.remaining_amount as u128).unwrap()).unwrap() as u64
-            } else {
-                (match_result.remaining_amount as u128)
-                    .checked_mul(market.yes_pool as u128).unwrap()
-                    .checked_div((market.no_pool as u128).checked_add(match_result.remaining_amount as u128).unwrap()).unwrap() as u64
-            };
-            
-            if outcome {
-                // Sell YES: remove from NO pool (collateral)
-                market.no_pool = market.no_pool.checked_sub(payout).unwrap();
-            } else {
-                // Sell NO: remove from YES pool (collateral)
-                market.yes_pool = market.yes_pool.checked_sub(payout).unwrap();
-            }
+            let (payout, yes_pool, no_pool) =
+                cpmm_sell(market.yes_pool, market.no_pool, match_result.remaining_amount, outcome);
+            market.yes_pool = yes_pool;
+            market.no_pool = no_pool;
             total_payout += payout;
         }
 
@@ -239,9 +324,42 @@ pub mod likeli_contracts {
         } else {
             user_position.no_shares = user_position.no_shares.checked_sub(shares_to_sell).unwrap();
         }
+        user_position.reduce_cost_basis(final_payout, &mut market.refund_basis);
 
         market.total_volume = market.total_volume.checked_add(final_payout).unwrap();
 
+        // Proceeds from bids move from escrow into the market vault, which
+        // pays the trader; the fee stays behind in the vault
+        let market_key = market.key();
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            match_result.direct_notional(),
+        )?;
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            &match_result,
+            false,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            final_payout,
+        )?;
+
         msg!(
             "Sold {} shares ({} matched) of {} for {} (min: {})",
             shares_to_sell,
@@ -256,15 +374,16 @@ pub mod likeli_contracts {
 
     /// Claim winnings after market resolution (legacy - no token transfer)
     pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let user_position = &mut ctx.accounts.user_position;
         
         require!(market.resolved, LikeliError::MarketNotResolved);
         
-        let winning_shares = if market.outcome {
-            user_position.yes_shares
-        } else {
-            user_position.no_shares
+        // Winning shares pay 1:1; an invalid market refunds the cost basis
+        let refund_weight = user_position.cost_basis.max(0) as u64;
+        let winning_shares = match market.outcome {
+            Some(MarketOutcome::Invalid) => refund_weight,
+            outcome => outcome.map_or(0, |outcome| user_position.payout(outcome)),
         };
         
         require!(winning_shares > 0, LikeliError::NoWinningShares);
@@ -274,22 +393,33 @@ pub mod likeli_contracts {
         // Zero out position
         user_position.yes_shares = 0;
         user_position.no_shares = 0;
+        user_position.take_refund_weight(&mut market.refund_basis);
         
         msg!("Claimed {} winnings from market {} (legacy)", payout, market.key());
         Ok(())
     }
 
     /// Claim winnings with actual token transfer from vault
+    /// An N/A refund needs the market's orderbook, if it has one, to show the
+    /// claimer has nothing resting or unsettled that would still move their basis
     pub fn claim_winnings_with_vault(ctx: Context<ClaimWinningsWithVault>) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let user_position = &mut ctx.accounts.user_position;
         
         require!(market.resolved, LikeliError::MarketNotResolved);
+        if market.outcome == Some(MarketOutcome::Invalid) && market.orderbook != Pubkey::default() {
+            let orderbook = ctx.accounts.orderbook.as_ref().ok_or(LikeliError::OrderbookMismatch)?;
+            require_book_idle(&*orderbook.load()?, &user_position.owner)?;
+        }
         
-        let winning_shares = if market.outcome {
-            user_position.yes_shares
-        } else {
-            user_position.no_shares
+        // Winning shares pay 1:1; an invalid market shares out the vault in
+        // proportion to each position's cost basis
+        let refund_weight = user_position.cost_basis.max(0) as u64;
+        let winning_shares = match market.outcome {
+            Some(MarketOutcome::Invalid) => {
+                refund_share(refund_weight, ctx.accounts.vault_ata.amount, market.refund_basis)
+            }
+            outcome => outcome.map_or(0, |outcome| user_position.payout(outcome)),
         };
         
         require!(winning_shares > 0, LikeliError::NoWinningShares);
@@ -300,6 +430,7 @@ pub mod likeli_contracts {
         // Zero out position before transfer (reentrancy protection)
         user_position.yes_shares = 0;
         user_position.no_shares = 0;
+        user_position.take_refund_weight(&mut market.refund_basis);
         
         // Transfer tokens from vault to user
         let market_key = market.key();
@@ -330,21 +461,156 @@ pub mod likeli_contracts {
     }
 
     /// Resolve a binary market
+    /// `Invalid` cancels the market: traders are refunded their net collateral spent.
+    /// `Probability(bps)` resolves MKT: YES shares pay `bps`, NO shares the rest
     pub fn resolve_market(
         ctx: Context<ResolveMarket>,
-        outcome: bool,
+        outcome: MarketOutcome,
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let clock = Clock::get()?;
 
         require!(!market.resolved, LikeliError::MarketResolved);
-        require!(ctx.accounts.resolver.key() == market.creator, LikeliError::Unauthorized);
+        require!(ctx.accounts.resolver.key() == market.resolver, LikeliError::Unauthorized);
         require!(clock.unix_timestamp >= market.resolution_time, LikeliError::TooEarlyToResolve);
+        require!(!market.proposal_pending, LikeliError::ProposalPending);
+        require!(market.price_feed == Pubkey::default(), LikeliError::PriceFeedMarket);
 
-        market.resolved = true;
-        market.outcome = outcome;
+        apply_market_resolution(market, outcome)
+    }
+
+    /// Resolve a binary market before its resolution time, for events that
+    /// happened ahead of schedule. Resolver only; trading is already halted
+    /// by the resolution itself.
+    pub fn resolve_market_early(
+        ctx: Context<ResolveMarket>,
+        outcome: MarketOutcome,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(ctx.accounts.resolver.key() == market.resolver, LikeliError::Unauthorized);
+        require!(clock.unix_timestamp < market.resolution_time, LikeliError::NotEarly);
+        require!(!market.proposal_pending, LikeliError::ProposalPending);
+        require!(market.price_feed == Pubkey::default(), LikeliError::PriceFeedMarket);
+
+        market.close_time = market.close_time.min(clock.unix_timestamp);
+        apply_market_resolution(market, outcome)
+    }
+
+    /// Set when trading halts on a binary market, at or before the resolution time
+    pub fn set_close_time(ctx: Context<SetMarketFees>, close_time: i64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(
+            close_time >= clock.unix_timestamp && close_time <= market.resolution_time,
+            LikeliError::InvalidCloseTime
+        );
+
+        market.close_time = close_time;
+
+        msg!("Trading on {} closes at {}", market.key(), close_time);
+        Ok(())
+    }
+
+    // ============== BINARY LIQUIDITY ==============
+
+    /// Add liquidity to a binary market's pools at the current price.
+    /// The deposit mints complete sets; both pools grow by the same factor and
+    /// the sets the smaller pool doesn't absorb go to the provider as shares.
+    pub fn add_liquidity(ctx: Context<BinaryLiquidity>, amount: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let lp_position = &mut ctx.accounts.lp_position;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
+        require!(amount > 0, LikeliError::InvalidAmount);
+
+        let (yes_added, no_added, lp_shares) =
+            lp_deposit(market.yes_pool, market.no_pool, market.total_lp_shares, amount);
+        require!(lp_shares > 0, LikeliError::InvalidAmount);
+
+        market.yes_pool = market.yes_pool.checked_add(yes_added).unwrap();
+        market.no_pool = market.no_pool.checked_add(no_added).unwrap();
+        market.total_lp_shares = market.total_lp_shares.checked_add(lp_shares).unwrap();
+
+        lp_position.owner = ctx.accounts.provider.key();
+        lp_position.market = market.key();
+        lp_position.shares = lp_position.shares.checked_add(lp_shares).unwrap();
+        lp_position.bump = ctx.bumps.lp_position;
+
+        user_position.owner = ctx.accounts.provider.key();
+        user_position.market = market.key();
+        user_position.add_shares(true, amount - yes_added);
+        user_position.add_shares(false, amount - no_added);
+        user_position.add_cost_basis(amount, &mut market.refund_basis);
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.provider_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.provider,
+            amount,
+        )?;
+
+        msg!("Added {} liquidity to {} for {} LP shares", amount, market.key(), lp_shares);
+        Ok(())
+    }
+
+    /// Withdraw a proportional share of a binary market's pools. Matching
+    /// YES/NO pairs are merged back into collateral; the excess of the larger
+    /// pool is credited to the provider's position as outcome shares.
+    pub fn remove_liquidity(ctx: Context<BinaryLiquidity>, lp_shares: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let lp_position = &mut ctx.accounts.lp_position;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(lp_shares > 0, LikeliError::InvalidAmount);
+        require!(lp_position.shares >= lp_shares, LikeliError::InsufficientLpShares);
+        // An N/A market refunds deposits through the cost basis instead
+        require!(market.outcome != Some(MarketOutcome::Invalid), LikeliError::LiquidityRefunded);
+        // The pools must stay priced while the market trades
+        require!(
+            market.resolved || lp_shares < market.total_lp_shares,
+            LikeliError::InsufficientLiquidity
+        );
+
+        let (yes_out, no_out) = lp_withdrawal(market.yes_pool, market.no_pool, market.total_lp_shares, lp_shares);
+        let collateral_out = yes_out.min(no_out);
+
+        market.yes_pool -= yes_out;
+        market.no_pool -= no_out;
+        market.total_lp_shares -= lp_shares;
+        lp_position.shares -= lp_shares;
 
-        msg!("Market resolved: {} -> {}", market.question, if outcome { "YES" } else { "NO" });
+        user_position.owner = ctx.accounts.provider.key();
+        user_position.market = market.key();
+        user_position.add_shares(true, yes_out - collateral_out);
+        user_position.add_shares(false, no_out - collateral_out);
+        user_position.reduce_cost_basis(collateral_out, &mut market.refund_basis);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.provider_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            collateral_out,
+        )?;
+
+        msg!(
+            "Removed {} LP shares from {}: {} collateral, {} YES, {} NO",
+            lp_shares,
+            market.key(),
+            collateral_out,
+            yes_out - collateral_out,
+            no_out - collateral_out
+        );
         Ok(())
     }
 
@@ -363,22 +629,41 @@ pub mod likeli_contracts {
         let market = &mut ctx.accounts.market;
         let clock = Clock::get()?;
 
-        require!(answer_count >= 2 && answer_count <= 10, LikeliError::InvalidAnswerCount);
+        require!((2..=10).contains(&answer_count), LikeliError::InvalidAnswerCount);
         require!(resolution_time > clock.unix_timestamp, LikeliError::InvalidResolutionTime);
         require!(initial_liquidity >= 100, LikeliError::InsufficientLiquidity);
         require!(fee_bps <= 1000, LikeliError::FeesTooHigh);
 
         market.creator = ctx.accounts.creator.key();
+        market.resolver = ctx.accounts.creator.key();
         market.question_hash = question_hash;
         market.answer_count = answer_count;
         market.is_one_winner = is_one_winner;
         market.volume = 0;
         market.fee_bps = fee_bps;
         market.resolution_time = resolution_time;
+        market.close_time = resolution_time;
         market.resolved = false;
         market.created_at = clock.unix_timestamp;
         market.bump = ctx.bumps.market;
         market.answers_resolved = 0;
+        market.winning_answer = None;
+        market.resolved_mask = 0;
+        market.yes_mask = 0;
+        market.invalid_mask = 0;
+        market.orderbooks = [Pubkey::default(); 10];
+        market.tick_size = 1;
+        market.min_order_qty = 1;
+        market.oracle_bond = 0;
+        market.challenge_window = DEFAULT_CHALLENGE_WINDOW;
+        market.committee = Pubkey::default();
+        market.resolution_grace = DEFAULT_RESOLUTION_GRACE;
+        market.abandoned = false;
+        market.pricing = PricingEngine::Cpmm;
+        market.lmsr_b = 0;
+        market.lmsr_q = [0; 10];
+        market.refund_basis = [0; 10];
+        market.answer_collateral = [0; 10];
 
         msg!("Multi-choice market created: {} answers, one_winner={}", answer_count, is_one_winner);
         Ok(())
@@ -395,6 +680,24 @@ pub mod likeli_contracts {
         Ok(())
     }
 
+    /// Create the orderbook for one answer of a multi-choice market. Creator only.
+    /// Allocated by the client like the binary orderbook
+    pub fn create_multi_orderbook(ctx: Context<CreateMultiOrderbook>, answer_index: u8) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(answer_index < market.answer_count, LikeliError::InvalidAnswerIndex);
+        let idx = answer_index as usize;
+        require_keys_eq!(market.orderbooks[idx], Pubkey::default(), LikeliError::OrderbookExists);
+
+        let mut orderbook = ctx.accounts.orderbook.load_init()?;
+        orderbook.market = market.key();
+        orderbook.answer_index = answer_index;
+        orderbook.is_multi = 1;
+        market.orderbooks[idx] = ctx.accounts.orderbook.key();
+
+        msg!("Orderbook {} created for answer {} of market {}", market.orderbooks[idx], answer_index, orderbook.market);
+        Ok(())
+    }
+
     /// Add an answer to a multi-choice market
     pub fn add_answer(
         ctx: Context<AddAnswer>,
@@ -402,7 +705,7 @@ pub mod likeli_contracts {
         label_hash: [u8; 32],
         initial_liquidity: u64,
     ) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let answer = &mut ctx.accounts.answer;
 
         require!(index < market.answer_count, LikeliError::InvalidAnswerIndex);
@@ -414,15 +717,165 @@ pub mod likeli_contracts {
         
         // Correct NegRisk initialization: starting price should be 1 / answer_count
         // Price P = no_pool / (yes_pool + no_pool)
-        // Set no_pool = liquidity, yes_pool = (N-1) * liquidity
-        answer.no_pool = initial_liquidity;
-        answer.yes_pool = initial_liquidity.checked_mul(market.answer_count as u64 - 1).unwrap();
+        // Set yes_pool = liquidity, no_pool = liquidity / (N-1), so the
+        // deposit collateralizes the larger pool exactly
+        answer.yes_pool = initial_liquidity;
+        answer.no_pool = (initial_liquidity / (market.answer_count as u64 - 1).max(1)).max(1);
+        answer.total_lp_shares = initial_liquidity;
         
         answer.volume = 0;
         answer.resolved = false;
         answer.outcome = None;
+        answer.proposal_pending = false;
+
+        // The creator's seed is an LP deposit: NO shares the pool doesn't hold go to their position
+        let lp_position = &mut ctx.accounts.lp_position;
+        lp_position.owner = ctx.accounts.creator.key();
+        lp_position.market = answer.key();
+        lp_position.shares = initial_liquidity;
+        lp_position.bump = ctx.bumps.lp_position;
+
+        let position = &mut ctx.accounts.position;
+        position.owner = ctx.accounts.creator.key();
+        position.market = market.key();
+        position.add_shares(index, false, initial_liquidity - answer.no_pool);
+        position.add_cost_basis(index, initial_liquidity, &mut market.refund_basis[index as usize]);
+        market.deposit_collateral(index, initial_liquidity);
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.creator_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.creator,
+            initial_liquidity,
+        )?;
+
+        msg!("Answer {} added to market with {} liquidity", index, initial_liquidity);
+        Ok(())
+    }
 
-        msg!("Answer {} added to market", index);
+    /// Add liquidity to an answer's pools at its current price. Both pools
+    /// grow by the same factor, so the answer's price — and with it the
+    /// NegRisk sum kept by `sync_sibling_pools` — is unchanged. Sets the
+    /// smaller pool doesn't absorb go to the provider as shares.
+    pub fn add_answer_liquidity(ctx: Context<AnswerLiquidity>, amount: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let answer = &mut ctx.accounts.answer;
+        let lp_position = &mut ctx.accounts.lp_position;
+        let position = &mut ctx.accounts.position;
+
+        require!(market.pricing == PricingEngine::Cpmm, LikeliError::WrongPricingEngine);
+        require!(!answer.resolved, LikeliError::AnswerAlreadyResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
+        require!(amount > 0, LikeliError::InvalidAmount);
+
+        let (yes_added, no_added, lp_shares) =
+            lp_deposit(answer.yes_pool, answer.no_pool, answer.total_lp_shares, amount);
+        require!(lp_shares > 0, LikeliError::InvalidAmount);
+
+        answer.yes_pool = answer.yes_pool.checked_add(yes_added).unwrap();
+        answer.no_pool = answer.no_pool.checked_add(no_added).unwrap();
+        answer.total_lp_shares = answer.total_lp_shares.checked_add(lp_shares).unwrap();
+
+        lp_position.owner = ctx.accounts.provider.key();
+        lp_position.market = answer.key();
+        lp_position.shares = lp_position.shares.checked_add(lp_shares).unwrap();
+        lp_position.bump = ctx.bumps.lp_position;
+
+        position.owner = ctx.accounts.provider.key();
+        position.market = market.key();
+        position.add_shares(answer.index, true, amount - yes_added);
+        position.add_shares(answer.index, false, amount - no_added);
+        position.add_cost_basis(answer.index, amount, &mut market.refund_basis[answer.index as usize]);
+        market.deposit_collateral(answer.index, amount);
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.provider_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.provider,
+            amount,
+        )?;
+
+        msg!("Added {} liquidity to answer {} for {} LP shares", amount, answer.index, lp_shares);
+        Ok(())
+    }
+
+    /// Withdraw a proportional share of an answer's pools. While the answer
+    /// trades, matching YES/NO pairs are merged back into collateral and the
+    /// excess is credited as shares; once it resolves, the winning side is
+    /// paid out. One-winner pools only pay out once the market resolves.
+    pub fn remove_answer_liquidity(ctx: Context<AnswerLiquidity>, lp_shares: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let answer = &mut ctx.accounts.answer;
+        let lp_position = &mut ctx.accounts.lp_position;
+        let position = &mut ctx.accounts.position;
+        let idx = answer.index as usize;
+        let outcome = market.answer_outcome(answer.index);
+
+        require!(lp_shares > 0, LikeliError::InvalidAmount);
+        require!(lp_position.shares >= lp_shares, LikeliError::InsufficientLpShares);
+        // An N/A answer refunds deposits through the cost basis instead
+        require!(outcome != Some(MarketOutcome::Invalid), LikeliError::LiquidityRefunded);
+        if outcome.is_none() {
+            require!(market.pricing == PricingEngine::Cpmm, LikeliError::WrongPricingEngine);
+            // Syncing siblings moves one-winner pools without moving collateral
+            require!(!market.is_one_winner, LikeliError::LiquidityLocked);
+            // The pools must stay priced while the answer trades
+            require!(lp_shares < answer.total_lp_shares, LikeliError::InsufficientLiquidity);
+        } else {
+            // Until then an abandoned one-winner market turns NO answers N/A
+            require!(market.resolved || !market.is_one_winner, LikeliError::MarketNotResolved);
+        }
+
+        let (yes_out, no_out) = lp_withdrawal(answer.yes_pool, answer.no_pool, answer.total_lp_shares, lp_shares);
+        let (collateral_out, yes_credit, no_credit) = match outcome {
+            None => {
+                let sets = yes_out.min(no_out);
+                (sets, yes_out - sets, no_out - sets)
+            }
+            Some(outcome) => {
+                let value = if outcome == MarketOutcome::Yes { yes_out } else { no_out };
+                // Synced CPMM pools can claim more than the answer's collateral; LMSR
+                // pools keep their seed while trades draw on the subsidy
+                let payout = match market.pricing {
+                    PricingEngine::Cpmm => value.min(market.answer_collateral[idx]),
+                    PricingEngine::Lmsr => value,
+                };
+                (payout, 0, 0)
+            }
+        };
+
+        answer.yes_pool -= yes_out;
+        answer.no_pool -= no_out;
+        answer.total_lp_shares -= lp_shares;
+        lp_position.shares -= lp_shares;
+
+        position.owner = ctx.accounts.provider.key();
+        position.market = market.key();
+        position.add_shares(answer.index, true, yes_credit);
+        position.add_shares(answer.index, false, no_credit);
+        position.reduce_cost_basis(answer.index, collateral_out, &mut market.refund_basis[answer.index as usize]);
+        market.withdraw_collateral(answer.index, collateral_out);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.provider_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            collateral_out,
+        )?;
+
+        msg!(
+            "Removed {} LP shares from answer {}: {} collateral, {} YES, {} NO",
+            lp_shares,
+            answer.index,
+            collateral_out,
+            yes_credit,
+            no_credit
+        );
         Ok(())
     }
 
@@ -436,15 +889,17 @@ pub mod likeli_contracts {
         let market = &mut ctx.accounts.market;
         let answer = &mut ctx.accounts.answer;
         let position = &mut ctx.accounts.position;
-        let orderbook = &ctx.accounts.orderbook;
         let remaining_accounts = ctx.remaining_accounts;
 
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(amount > 0, LikeliError::InvalidAmount);
 
-        // Security: Max trade size = 25% of pool to prevent manipulation
-        let total_pool = answer.yes_pool.checked_add(answer.no_pool).unwrap();
-        let max_trade = total_pool / 4;
+        // Security: Max trade size = 25% of pool (b under LMSR) to prevent manipulation
+        let max_trade = match market.pricing {
+            PricingEngine::Cpmm => answer.yes_pool.checked_add(answer.no_pool).unwrap() / 4,
+            PricingEngine::Lmsr => market.lmsr_b,
+        };
         require!(amount <= max_trade, LikeliError::TradeTooLarge);
 
         let fee = calculate_fee(amount, market.fee_bps);
@@ -453,45 +908,42 @@ pub mod likeli_contracts {
         msg!("BuyMulti: is_one_winner={}, outcome={}, amount={}, answer={}", market.is_one_winner, outcome, amount, answer.index);
 
         // 1. Try to match against orderbook
-        let total_pool = answer.yes_pool.checked_add(answer.no_pool).unwrap();
-        let cpmm_price = if outcome {
-            (answer.no_pool as u128 * 10000 / total_pool as u128) as u64
-        } else {
-            (answer.yes_pool as u128 * 10000 / total_pool as u128) as u64
-        };
+        let amm_price = multi_amm_price(market, answer, outcome)?;
 
         let match_result = try_match_against_orderbook(
-            orderbook, 
-            remaining_accounts, 
-            Some(answer.index),
+            ctx.accounts.orderbook.as_ref(),
+            market.orderbooks[answer.index as usize],
             outcome, 
             true, // is_buy
-            cpmm_price, 
-            amount_after_fee
+            amm_price, 
+            amount_after_fee,
+            ctx.accounts.buyer.key(),
+            market.min_order_qty,
         )?;
 
-        let mut total_shares = 0;
-
-        if match_result.filled_amount > 0 {
-            let matched_shares = (match_result.filled_amount as u128 * 10000 / cpmm_price.max(1) as u128) as u64;
-            total_shares += matched_shares;
-        }
+        // Shares bought from resting asks at the makers' prices
+        let mut total_shares = match_result.filled_amount;
 
         if match_result.remaining_amount > 0 {
-            let shares = calculate_shares_out(answer.yes_pool, answer.no_pool, match_result.remaining_amount, outcome);
-            
-            if outcome {
-                // Buy YES: add to NO pool to increase price
-                answer.no_pool = answer.no_pool.checked_add(match_result.remaining_amount).unwrap();
-            } else {
-                // Buy NO: add to YES pool to increase price
-                answer.yes_pool = answer.yes_pool.checked_add(match_result.remaining_amount).unwrap();
-            }
+            let shares = match market.pricing {
+                PricingEngine::Lmsr => {
+                    let shares = lmsr_shares_out(market, answer.index, match_result.remaining_amount, outcome)?;
+                    lmsr_apply(market, answer.index, shares, outcome, true);
+                    shares
+                }
+                PricingEngine::Cpmm => {
+                    let (shares, yes_pool, no_pool) =
+                        cpmm_buy(answer.yes_pool, answer.no_pool, match_result.remaining_amount, outcome);
+                    answer.yes_pool = yes_pool;
+                    answer.no_pool = no_pool;
+                    shares
+                }
+            };
             total_shares += shares;
         }
 
-        // 3. NegRisk Rebalancing if enabled
-        if market.is_one_winner {
+        // 3. NegRisk Rebalancing if enabled (LMSR prices already sum to one)
+        if market.is_one_winner && market.pricing == PricingEngine::Cpmm {
             let total = answer.yes_pool.checked_add(answer.no_pool).unwrap();
             let new_price = if outcome {
                 (answer.no_pool as u128 * 10000 / total as u128) as u64
@@ -512,9 +964,41 @@ pub mod likeli_contracts {
 
         position.owner = ctx.accounts.buyer.key();
         position.market = market.key();
+        position.add_cost_basis(answer.index, amount, &mut market.refund_basis[idx]);
+        market.deposit_collateral(
+            answer.index,
+            amount - match_result.direct_notional() + match_result.maker_set_notional,
+        );
         answer.volume = answer.volume.checked_add(amount).unwrap();
         market.volume = market.volume.checked_add(amount).unwrap();
 
+        // Collateral matched against asks goes to the order vault for makers to
+        // settle; the rest (minted sets, pool trade, fee) goes to the market vault
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.buyer,
+            match_result.direct_notional(),
+        )?;
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.buyer,
+            amount - match_result.direct_notional(),
+        )?;
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            &match_result,
+            true,
+        )?;
+
         msg!("Bought {} shares ({} matched) of {} on answer {}. New Pools: Y={}, N={}", 
              total_shares, match_result.filled_amount, if outcome { "YES" } else { "NO" }, answer.index, answer.yes_pool, answer.no_pool);
         Ok(())
@@ -524,10 +1008,10 @@ pub mod likeli_contracts {
     pub fn rebalance_market(ctx: Context<BuyMulti>) -> Result<()> {
         let market = &ctx.accounts.market;
         let answer = &ctx.accounts.answer;
-        let _orderbook = &ctx.accounts.orderbook;
         let remaining_accounts = ctx.remaining_accounts;
 
         require!(market.is_one_winner, LikeliError::NotOneWinnerMarket);
+        require!(market.pricing == PricingEngine::Cpmm, LikeliError::WrongPricingEngine);
         
         let total = answer.yes_pool.checked_add(answer.no_pool).unwrap();
         let current_price = (answer.no_pool as u128 * 10000 / total as u128) as u64;
@@ -547,15 +1031,99 @@ pub mod likeli_contracts {
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        // Positions and resolutions so far were priced and settled under the current mode
+        require!(
+            is_one_winner == market.is_one_winner || (market.volume == 0 && market.answers_resolved == 0),
+            LikeliError::OneWinnerLocked
+        );
+        // LMSR prices answers as mutually exclusive
+        require!(
+            is_one_winner || market.pricing == PricingEngine::Cpmm,
+            LikeliError::NotOneWinnerMarket
+        );
+        // Once the resolution time has passed it can be brought forward, not pushed back
+        require!(
+            resolution_time <= market.resolution_time || Clock::get()?.unix_timestamp < market.resolution_time,
+            LikeliError::InvalidResolutionTime
+        );
         
         market.is_one_winner = is_one_winner;
         market.fee_bps = fee_bps;
+        // A close time left at its default follows the resolution time
+        if market.close_time == market.resolution_time || market.close_time > resolution_time {
+            market.close_time = resolution_time;
+        }
         market.resolution_time = resolution_time;
         
         msg!("Multi-market config updated for {}", market.key());
         Ok(())
     }
 
+    /// Set the tick size (bps) and minimum order size for a multi-choice market's orderbooks
+    pub fn set_multi_order_limits(
+        ctx: Context<SetMultiMarketConfig>,
+        tick_size: u16,
+        min_order_qty: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(tick_size > 0 && tick_size < 10000, LikeliError::InvalidTickSize);
+        require!(min_order_qty > 0, LikeliError::InvalidAmount);
+
+        market.tick_size = tick_size;
+        market.min_order_qty = min_order_qty;
+
+        msg!("Multi-market order limits updated: tick {}bps, min qty {}", tick_size, min_order_qty);
+        Ok(())
+    }
+
+    /// Hand a multi-choice market's resolution authority to another key.
+    /// Only possible before any trading.
+    pub fn set_multi_resolver(ctx: Context<SetMultiMarketConfig>, resolver: Pubkey) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.volume == 0, LikeliError::MarketHasVolume);
+
+        market.resolver = resolver;
+
+        msg!("Resolver for {} set to {}", market.key(), resolver);
+        Ok(())
+    }
+
+    /// Switch a one-winner market to LMSR pricing with liquidity parameter `b`.
+    /// The creator deposits the maximum market-maker loss, b·ln(N), up front.
+    /// Only possible before any trading.
+    pub fn enable_lmsr(ctx: Context<EnableLmsr>, b: u64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.is_one_winner, LikeliError::NotOneWinnerMarket);
+        require!(market.volume == 0, LikeliError::MarketHasVolume);
+        require!(market.pricing == PricingEngine::Cpmm, LikeliError::WrongPricingEngine);
+        require!((100..=MAX_LMSR_B).contains(&b), LikeliError::InvalidLiquidityParameter);
+
+        // Round the subsidy up so the vault always covers the worst case
+        let ln_n = ln_wad(market.answer_count as i128 * WAD)?;
+        let subsidy = (b as i128 * ln_n / WAD) as u64 + 1;
+
+        market.pricing = PricingEngine::Lmsr;
+        market.lmsr_b = b;
+        market.lmsr_q = [0; 10];
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.creator_ata,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.creator,
+            subsidy,
+        )?;
+
+        msg!("LMSR pricing enabled for {}: b={}, subsidy={}", market.key(), b, subsidy);
+        Ok(())
+    }
+
     /// Sell shares in a multi-choice answer
     pub fn sell_multi(
         ctx: Context<BuyMulti>,
@@ -566,10 +1134,10 @@ pub mod likeli_contracts {
         let market = &mut ctx.accounts.market;
         let answer = &mut ctx.accounts.answer;
         let position = &mut ctx.accounts.position;
-        let orderbook = &ctx.accounts.orderbook;
         let remaining_accounts = ctx.remaining_accounts;
 
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(Clock::get()?.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(shares_to_sell > 0, LikeliError::InvalidAmount);
 
         let idx = answer.index as usize;
@@ -578,57 +1146,49 @@ pub mod likeli_contracts {
         } else {
             require!(position.no_shares[idx] >= shares_to_sell, LikeliError::InsufficientShares);
         }
+        if market.pricing == PricingEngine::Lmsr {
+            require!(shares_to_sell <= market.lmsr_b, LikeliError::TradeTooLarge);
+        }
 
         // 1. Try to match against orderbook bids
-        let total_pool = answer.yes_pool.checked_add(answer.no_pool).unwrap();
-        let cpmm_price = if outcome {
-            (answer.no_pool as u128 * 10000 / total_pool as u128) as u64
-        } else {
-            (answer.yes_pool as u128 * 10000 / total_pool as u128) as u64
-        };
+        let amm_price = multi_amm_price(market, answer, outcome)?;
 
         let match_result = try_match_against_orderbook(
-            orderbook, 
-            ctx.remaining_accounts, 
-            Some(answer.index),
+            ctx.accounts.orderbook.as_ref(),
+            market.orderbooks[answer.index as usize],
             outcome, 
             false, // is_buy = false (Selling)
-            cpmm_price, 
-            shares_to_sell
+            amm_price, 
+            shares_to_sell,
+            ctx.accounts.buyer.key(),
+            market.min_order_qty,
         )?;
 
         let mut total_payout = 0;
 
-        if match_result.filled_amount > 0 {
-            let matched_payout = (match_result.filled_amount as u128 * cpmm_price as u128 / 10000) as u64;
-            total_payout += matched_payout;
-        }
+        // Collateral from resting bids at the makers' prices
+        total_payout += match_result.notional;
 
         if match_result.remaining_amount > 0 {
-            // Sell YES for collateral: use discrete payout formula
-            // Payout = shares * no_pool / (yes_pool + shares)
-            let payout = if outcome {
-                (match_result.remaining_amount as u128)
-                    .checked_mul(answer.no_pool as u128).unwrap()
-                    .checked_div((answer.yes_pool as u128).checked_add(match_result.remaining_amount as u128).unwrap()).unwrap() as u64
-            } else {
-                (match_result.remaining_amount as u128)
-                    .checked_mul(answer.yes_pool as u128).unwrap()
-                    .checked_div((answer.no_pool as u128).checked_add(match_result.remaining_amount as u128).unwrap()).unwrap() as u64
+            let payout = match market.pricing {
+                PricingEngine::Lmsr => {
+                    let payout = lmsr_payout(market, answer.index, match_result.remaining_amount, outcome)?;
+                    lmsr_apply(market, answer.index, match_result.remaining_amount, outcome, false);
+                    payout
+                }
+                PricingEngine::Cpmm => {
+                    let (payout, yes_pool, no_pool) =
+                        cpmm_sell(answer.yes_pool, answer.no_pool, match_result.remaining_amount, outcome);
+                    answer.yes_pool = yes_pool;
+                    answer.no_pool = no_pool;
+                    payout
+                }
             };
-            
-            if outcome {
-                // Sell YES: remove from NO pool (collateral)
-                answer.no_pool = answer.no_pool.checked_sub(payout).unwrap();
-            } else {
-                // Sell NO: remove from YES pool (collateral)
-                answer.yes_pool = answer.yes_pool.checked_sub(payout).unwrap();
-            }
             total_payout += payout;
         }
 
-        // 3. NegRisk Rebalancing if enabled
-        if market.is_one_winner {
+        // 3. NegRisk Rebalancing if enabled (LMSR prices already sum to one)
+        if market.is_one_winner && market.pricing == PricingEngine::Cpmm {
             let total = answer.yes_pool.checked_add(answer.no_pool).unwrap();
             let new_price = if outcome {
                 (answer.no_pool as u128 * 10000 / total as u128) as u64
@@ -649,10 +1209,45 @@ pub mod likeli_contracts {
         } else {
             position.no_shares[idx] = position.no_shares[idx].checked_sub(shares_to_sell).unwrap();
         }
+        position.reduce_cost_basis(answer.index, final_payout, &mut market.refund_basis[idx]);
+        market.deposit_collateral(answer.index, match_result.direct_notional());
+        market.withdraw_collateral(answer.index, match_result.maker_set_notional + final_payout);
 
         answer.volume = answer.volume.checked_add(final_payout).unwrap();
         market.volume = market.volume.checked_add(final_payout).unwrap();
 
+        // Proceeds from bids move from escrow into the market vault, which
+        // pays the trader; the fee stays behind in the vault
+        let market_key = market.key();
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            match_result.direct_notional(),
+        )?;
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            &match_result,
+            false,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.buyer_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            final_payout,
+        )?;
+
         msg!("Sold {} shares ({} matched) of {} on answer {}", 
              shares_to_sell, match_result.filled_amount, if outcome { "YES" } else { "NO" }, answer.index);
         Ok(())
@@ -673,7 +1268,7 @@ pub mod likeli_contracts {
         index_set: u16,
         amount: u64,
     ) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let market_key = market.key();
         let position = &mut ctx.accounts.position;
 
@@ -724,39 +1319,38 @@ pub mod likeli_contracts {
 
         // Collateral out: (no_count - 1) × amount_after_fee
         let collateral_out = (no_count - 1).checked_mul(amount_after_fee).unwrap();
+        let total_fee = fee.checked_mul(no_count - 1).unwrap();
 
-        // Transfer fees to fee vault (if any)
-        if fee > 0 {
-            let fee_multiplier = no_count.checked_sub(1).unwrap_or(0);
-            let total_fee = fee.checked_mul(fee_multiplier).unwrap_or(0);
-            if total_fee > 0 {
-                let cpi_accounts = Transfer {
-                    from: ctx.accounts.vault_ata.to_account_info(),
-                    to: ctx.accounts.fee_vault_ata.to_account_info(),
-                    authority: ctx.accounts.vault_authority.to_account_info(),
-                };
-                let seeds = &[
-                    VAULT_SEED,
-                    market_key.as_ref(),
-                    &[ctx.bumps.vault_authority],
-                ];
-                let signer = &[&seeds[..]];
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        ctx.accounts.token_program.to_account_info(),
-                        cpi_accounts,
-                        signer,
-                    ),
-                    total_fee,
-                )?;
+        // Collateral leaving the vault, returned or taken as fees, comes off the
+        // cost basis and collateral of the burned NO positions; whatever exceeds
+        // their bases comes off the first of them
+        let mut unattributed = collateral_out + total_fee;
+        for i in 0..question_count {
+            if (index_set & (1 << i)) > 0 {
+                let idx = i as usize;
+                let refunded = unattributed.min(position.cost_basis[idx].max(0) as u64);
+                position.reduce_cost_basis(i as u8, refunded, &mut market.refund_basis[idx]);
+                unattributed -= refunded;
+            }
+        }
+        if unattributed > 0 {
+            let first = index_set.trailing_zeros() as u8;
+            position.reduce_cost_basis(first, unattributed, &mut market.refund_basis[first as usize]);
+        }
+        let mut withdrawn = collateral_out + total_fee;
+        for i in 0..question_count {
+            if (index_set & (1 << i)) > 0 {
+                let taken = withdrawn.min(market.answer_collateral[i as usize]);
+                market.withdraw_collateral(i as u8, taken);
+                withdrawn -= taken;
             }
         }
 
-        // Transfer collateral_out from vault to user
-        if collateral_out > 0 {
+        // Transfer fees to fee vault (if any)
+        if total_fee > 0 {
             let cpi_accounts = Transfer {
                 from: ctx.accounts.vault_ata.to_account_info(),
-                to: ctx.accounts.user_ata.to_account_info(),
+                to: ctx.accounts.fee_vault_ata.to_account_info(),
                 authority: ctx.accounts.vault_authority.to_account_info(),
             };
             let seeds = &[
@@ -771,14 +1365,37 @@ pub mod likeli_contracts {
                     cpi_accounts,
                     signer,
                 ),
-                collateral_out,
+                total_fee,
             )?;
         }
 
-        msg!(
-            "Converted {} NO positions. Collateral out: {}, YES shares to {} answers, Fee: {}",
-            no_count,
-            collateral_out,
+        // Transfer collateral_out from vault to user
+        if collateral_out > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.vault_ata.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            };
+            let seeds = &[
+                VAULT_SEED,
+                market_key.as_ref(),
+                &[ctx.bumps.vault_authority],
+            ];
+            let signer = &[&seeds[..]];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    cpi_accounts,
+                    signer,
+                ),
+                collateral_out,
+            )?;
+        }
+
+        msg!(
+            "Converted {} NO positions. Collateral out: {}, YES shares to {} answers, Fee: {}",
+            no_count,
+            collateral_out,
             yes_count,
             fee
         );
@@ -792,6 +1409,7 @@ pub mod likeli_contracts {
         ctx: Context<SplitPositionWithVault>,
         amount: u64,
     ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
         let answer = &ctx.accounts.answer;
         let position = &mut ctx.accounts.position;
 
@@ -815,6 +1433,8 @@ pub mod likeli_contracts {
         let idx = answer.index as usize;
         position.yes_shares[idx] = position.yes_shares[idx].checked_add(amount).unwrap();
         position.no_shares[idx] = position.no_shares[idx].checked_add(amount).unwrap();
+        position.add_cost_basis(answer.index, amount, &mut market.refund_basis[idx]);
+        market.deposit_collateral(answer.index, amount);
 
         msg!("Split {} collateral into YES+NO for answer {}", amount, answer.index);
         Ok(())
@@ -826,7 +1446,7 @@ pub mod likeli_contracts {
         ctx: Context<SplitPositionWithVault>,
         amount: u64,
     ) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let market_key = market.key();
         let answer = &ctx.accounts.answer;
         let position = &mut ctx.accounts.position;
@@ -840,6 +1460,8 @@ pub mod likeli_contracts {
         // Burn YES + NO shares
         position.yes_shares[idx] = position.yes_shares[idx].checked_sub(amount).unwrap();
         position.no_shares[idx] = position.no_shares[idx].checked_sub(amount).unwrap();
+        position.reduce_cost_basis(answer.index, amount, &mut market.refund_basis[idx]);
+        market.withdraw_collateral(answer.index, amount);
 
         // Transfer collateral FROM vault TO user
         let cpi_accounts = Transfer {
@@ -867,39 +1489,64 @@ pub mod likeli_contracts {
     }
 
     /// Resolve an answer in a multi-choice market
-    pub fn resolve_answer(
-        ctx: Context<ResolveAnswer>,
-        outcome: bool,
+    /// In one-winner markets, resolving an answer YES resolves every other
+    /// unresolved answer NO, and `Invalid` cancels the whole market; pass the
+    /// other answers as writable remaining accounts.
+    pub fn resolve_answer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveAnswer<'info>>,
+        outcome: MarketOutcome,
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let answer = &mut ctx.accounts.answer;
         let clock = Clock::get()?;
 
         require!(!answer.resolved, LikeliError::AnswerAlreadyResolved);
-        require!(ctx.accounts.resolver.key() == market.creator, LikeliError::Unauthorized);
+        require!(ctx.accounts.resolver.key() == market.resolver, LikeliError::Unauthorized);
         require!(clock.unix_timestamp >= market.resolution_time, LikeliError::TooEarlyToResolve);
+        require!(!answer.proposal_pending, LikeliError::ProposalPending);
 
-        // For one-winner markets: if one answer is YES, no other can be YES
-        if market.is_one_winner && outcome {
-            require!(market.answers_resolved == 0 || !has_winner(market), LikeliError::WinnerAlreadyDeclared);
-        }
+        apply_answer_resolution(market, answer, outcome, ctx.remaining_accounts)
+    }
+
+    /// Resolve an answer before the market's resolution time. Resolver only;
+    /// trading on the whole market halts from now on.
+    pub fn resolve_answer_early<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveAnswer<'info>>,
+        outcome: MarketOutcome,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let answer = &mut ctx.accounts.answer;
+        let clock = Clock::get()?;
 
-        answer.resolved = true;
-        answer.outcome = Some(outcome);
-        market.answers_resolved = market.answers_resolved.checked_add(1).unwrap();
+        require!(!answer.resolved, LikeliError::AnswerAlreadyResolved);
+        require!(ctx.accounts.resolver.key() == market.resolver, LikeliError::Unauthorized);
+        require!(clock.unix_timestamp < market.resolution_time, LikeliError::NotEarly);
+        require!(!answer.proposal_pending, LikeliError::ProposalPending);
 
-        // Check if all answers resolved
-        if market.answers_resolved == market.answer_count {
-            market.resolved = true;
-        }
+        market.close_time = market.close_time.min(clock.unix_timestamp);
+        apply_answer_resolution(market, answer, outcome, ctx.remaining_accounts)
+    }
+
+    /// Set when trading halts on a multi-choice market, at or before the resolution time
+    pub fn set_multi_close_time(ctx: Context<SetMultiMarketConfig>, close_time: i64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(
+            close_time >= clock.unix_timestamp && close_time <= market.resolution_time,
+            LikeliError::InvalidCloseTime
+        );
+
+        market.close_time = close_time;
 
-        msg!("Answer {} resolved: {}", answer.index, if outcome { "YES" } else { "NO" });
+        msg!("Trading on {} closes at {}", market.key(), close_time);
         Ok(())
     }
 
     /// Claim winnings from multi-choice market (legacy - no token transfer)
     pub fn claim_multi_winnings(ctx: Context<ClaimMultiWinnings>) -> Result<()> {
-        let market = &ctx.accounts.market;
+        let market = &mut ctx.accounts.market;
         let position = &mut ctx.accounts.position;
 
         require!(market.resolved, LikeliError::MarketNotResolved);
@@ -907,10 +1554,17 @@ pub mod likeli_contracts {
         let mut total_payout: u64 = 0;
 
         // Zero out position
-        for i in 0..10 {
-            total_payout = total_payout.checked_add(position.yes_shares[i]).unwrap_or(total_payout);
-            position.yes_shares[i] = 0;
-            position.no_shares[i] = 0;
+        for i in 0..market.answer_count {
+            let idx = i as usize;
+            let outcome = market.answer_outcome(i);
+            let refund_weight = position.take_refund_weight(i, &mut market.refund_basis[idx]);
+            let winning_shares = match outcome {
+                Some(MarketOutcome::Invalid) => refund_weight,
+                outcome => outcome.map_or(0, |outcome| position.payout(i, outcome)),
+            };
+            total_payout = total_payout.checked_add(winning_shares).unwrap();
+            position.yes_shares[idx] = 0;
+            position.no_shares[idx] = 0;
         }
 
         msg!("Claimed {} winnings from multi-choice market (legacy)", total_payout);
@@ -918,23 +1572,49 @@ pub mod likeli_contracts {
     }
 
     /// Claim winnings from multi-choice market with actual token transfer
-    pub fn claim_multi_winnings_with_vault(ctx: Context<ClaimMultiWinningsWithVault>) -> Result<()> {
-        let market = &ctx.accounts.market;
+    /// YES shares pay out for answers that resolved YES, NO shares for answers
+    /// that resolved NO, and invalid answers share out their collateral by
+    /// cost basis, read from the market's outcome bitmaps. Pass the orderbook
+    /// of every invalid answer that has one as a remaining account.
+    pub fn claim_multi_winnings_with_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimMultiWinningsWithVault<'info>>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
         let position = &mut ctx.accounts.position;
 
         require!(market.resolved, LikeliError::MarketNotResolved);
 
-        // Calculate total winnings across all answers
-        // For one-winner: only winning answer's YES shares pay out
-        // For multi-winner: each answer that resolved YES pays out
         let mut total_payout: u64 = 0;
 
-        // Sum up all YES shares as payout (1:1 with collateral)
-        // In production, check which answers resolved YES via remaining_accounts
-        for i in 0..10 {
-            total_payout = total_payout.checked_add(position.yes_shares[i]).unwrap_or(total_payout);
-            position.yes_shares[i] = 0;
-            position.no_shares[i] = 0;
+        for i in 0..market.answer_count {
+            let Some(outcome) = market.answer_outcome(i) else {
+                continue;
+            };
+            let idx = i as usize;
+            let book_key = market.orderbooks[idx];
+            if outcome == MarketOutcome::Invalid && book_key != Pubkey::default() {
+                let book_info = ctx
+                    .remaining_accounts
+                    .iter()
+                    .find(|info| info.key() == book_key)
+                    .ok_or(LikeliError::OrderbookMismatch)?;
+                let orderbook = AccountLoader::<Orderbook>::try_from(book_info)?;
+                require_book_idle(&*orderbook.load()?, &position.owner)?;
+            }
+            let payout = if outcome == MarketOutcome::Invalid {
+                let vault_left = ctx.accounts.vault_ata.amount.checked_sub(total_payout).unwrap();
+                let (pool, refund_basis) = market.refund_pool(i, vault_left);
+                refund_share(position.cost_basis[idx].max(0) as u64, pool, refund_basis)
+            } else {
+                position.payout(i, outcome)
+            };
+            total_payout = total_payout.checked_add(payout).unwrap();
+            market.withdraw_collateral(i, payout);
+
+            // Losing shares are worthless once the answer resolves
+            position.yes_shares[idx] = 0;
+            position.no_shares[idx] = 0;
+            position.take_refund_weight(i, &mut market.refund_basis[idx]);
         }
 
         require!(total_payout > 0, LikeliError::NoWinningShares);
@@ -969,66 +1649,150 @@ pub mod likeli_contracts {
 
     // ============== LIMIT ORDERS ==============
 
-    /// Place a limit order for a binary market
+    /// Place an order for a binary market
+    /// Crosses the book first; what happens to the unfilled remainder depends on `order_type`
+    #[allow(clippy::too_many_arguments)]
     pub fn place_order(
         ctx: Context<PlaceOrder>,
-        answer_index: Option<u8>,
         price: u64,
         qty: u64,
         is_yes: bool,
         is_bid: bool,
         expires_in: Option<i64>,
+        order_type: OrderType,
+        self_trade: SelfTradeMode,
     ) -> Result<()> {
-        let order = &mut ctx.accounts.order;
-        let market = &ctx.accounts.market;
-        let orderbook = &mut ctx.accounts.orderbook;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.position;
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
         let clock = Clock::get()?;
 
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(clock.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(qty > 0, LikeliError::InvalidAmount);
         require!(price > 0 && price < 10000, LikeliError::InvalidPrice);
+        check_order_rules(price, qty, market.tick_size, market.min_order_qty)?;
+        require!(expires_in.is_none_or(|ei| ei > 0), LikeliError::InvalidExpiry);
+        require!(expires_in.is_none() || order_type.rests(), LikeliError::InvalidExpiry);
+
+        if !is_bid {
+            require!(position.free_shares(is_yes) >= qty, LikeliError::InsufficientShares);
+        }
 
         // Try to match against book first
-        let match_result = find_matching_orders(
-            orderbook, 
-            ctx.remaining_accounts,
-            answer_index,
-            is_yes,
-            is_bid,
-            price,
-            qty
+        let match_result = match_incoming_order(
+            &mut orderbook,
+            &TakerOrder {
+                owner: ctx.accounts.owner.key(),
+                is_yes,
+                is_buy: is_bid,
+                limit_price: price,
+                amount: qty,
+                amount_is_collateral: false,
+                self_trade,
+            },
+            order_type,
+            clock.unix_timestamp,
+            market.min_order_qty,
         )?;
 
-        order.owner = ctx.accounts.owner.key();
-        order.market = market.key();
-        order.answer_index = answer_index;
-        order.price = price;
-        order.qty = qty;
-        order.filled_qty = match_result.filled_amount;
-        order.is_yes = is_yes;
-        order.is_bid = is_bid;
-        order.created_at = clock.unix_timestamp;
-        order.expires_at = expires_in.map(|ei| clock.unix_timestamp + ei);
-
-        // Only add to book if not fully filled
-        if order.filled_qty < order.qty {
-            let order_key = order.key();
-            let bucket = match (is_yes, is_bid) {
-                (true, true) => &mut orderbook.yes_buy_orders,
-                (true, false) => &mut orderbook.yes_sell_orders,
-                (false, true) => &mut orderbook.no_buy_orders,
-                (false, false) => &mut orderbook.no_sell_orders,
-            };
+        position.owner = ctx.accounts.owner.key();
+        position.market = market.key();
+
+        // Settle the immediately matched portion at the makers' prices
+        if is_bid {
+            position.add_shares(is_yes, match_result.filled_amount);
+            position.add_cost_basis(match_result.notional, &mut market.refund_basis);
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner,
+                match_result.direct_notional(),
+            )?;
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_ata,
+                &ctx.accounts.owner,
+                match_result.set_notional,
+            )?;
+        } else {
+            position.remove_shares(is_yes, match_result.filled_amount)?;
+            position.reduce_cost_basis(match_result.notional, &mut market.refund_basis);
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                match_result.direct_notional(),
+            )?;
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.vault_ata,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                match_result.set_notional,
+            )?;
+        }
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            &match_result,
+            is_bid,
+        )?;
+        market.total_volume = market.total_volume.checked_add(match_result.notional).unwrap();
 
-            require!(bucket.len() < 100, LikeliError::OrderbookFull);
-            bucket.push(order_key);
+        // Escrow the resting remainder: collateral for bids, shares for asks.
+        // Remainders below the minimum order size are dropped rather than rested
+        let resting_qty = if order_type.rests() && match_result.remaining_amount >= market.min_order_qty {
+            match_result.remaining_amount
+        } else {
+            0
+        };
+        if is_bid {
+            let escrow = order_notional(price, resting_qty);
+            require!(escrow > 0 || resting_qty == 0, LikeliError::InvalidAmount);
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner,
+                escrow,
+            )?;
+        } else {
+            position.lock_shares(is_yes, resting_qty)?;
         }
 
-        msg!("Order placed (matched {}): {}", match_result.filled_amount, order.key());
+        // Only add to book if something is left to rest
+        if resting_qty > 0 {
+            let expires_at = expires_in.map_or(0, |ei| clock.unix_timestamp + ei);
+            let order_id = orderbook.rest_order(
+                &ctx.accounts.owner.key(),
+                is_yes,
+                is_bid,
+                price,
+                resting_qty,
+                expires_at,
+            )?;
+            msg!("Order placed (matched {}): {}", match_result.filled_amount, order_id);
+        } else {
+            msg!("Order matched {} of {}", match_result.filled_amount, qty);
+        }
         Ok(())
     }
 
-    /// Place a limit order for a multi-choice market
+    /// Place an order for a multi-choice market
+    /// Crosses the answer's book first; what happens to the unfilled remainder depends on `order_type`
+    #[allow(clippy::too_many_arguments)]
     pub fn place_multi_order(
         ctx: Context<PlaceMultiOrder>,
         answer_index: u8,
@@ -1037,454 +1801,2829 @@ pub mod likeli_contracts {
         is_yes: bool,
         is_bid: bool,
         expires_in: Option<i64>,
+        order_type: OrderType,
+        self_trade: SelfTradeMode,
     ) -> Result<()> {
-        let order = &mut ctx.accounts.order;
-        let market = &ctx.accounts.market;
-        let orderbook = &mut ctx.accounts.orderbook;
+        let market = &mut ctx.accounts.market;
+        let position = &mut ctx.accounts.position;
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
         let clock = Clock::get()?;
 
         require!(!market.resolved, LikeliError::MarketResolved);
+        require!(clock.unix_timestamp < market.close_time, LikeliError::TradingClosed);
         require!(qty > 0, LikeliError::InvalidAmount);
         require!(price > 0 && price < 10000, LikeliError::InvalidPrice);
         
         // Verify answer index is within bounds
         require!(answer_index < market.answer_count, LikeliError::InvalidAnswerIndex);
+        check_order_rules(price, qty, market.tick_size, market.min_order_qty)?;
+        require!(expires_in.is_none_or(|ei| ei > 0), LikeliError::InvalidExpiry);
+        require!(expires_in.is_none() || order_type.rests(), LikeliError::InvalidExpiry);
+
+        if !is_bid {
+            require!(position.free_shares(answer_index, is_yes) >= qty, LikeliError::InsufficientShares);
+        }
 
         // Try to match against book first
-        let match_result = find_matching_orders(
-            orderbook, 
-            ctx.remaining_accounts,
-            Some(answer_index),
-            is_yes,
-            is_bid,
-            price,
-            qty
+        let match_result = match_incoming_order(
+            &mut orderbook,
+            &TakerOrder {
+                owner: ctx.accounts.owner.key(),
+                is_yes,
+                is_buy: is_bid,
+                limit_price: price,
+                amount: qty,
+                amount_is_collateral: false,
+                self_trade,
+            },
+            order_type,
+            clock.unix_timestamp,
+            market.min_order_qty,
         )?;
 
-        order.owner = ctx.accounts.owner.key();
-        order.market = market.key();
-        order.answer_index = Some(answer_index);
-        order.price = price;
-        order.qty = qty;
-        order.filled_qty = match_result.filled_amount;
-        order.is_yes = is_yes;
-        order.is_bid = is_bid;
-        order.created_at = clock.unix_timestamp;
-        order.expires_at = expires_in.map(|ei| clock.unix_timestamp + ei);
-
-        // Only add to book if not fully filled
-        if order.filled_qty < order.qty {
-            let order_key = order.key();
-            let bucket = match (is_yes, is_bid) {
-                (true, true) => &mut orderbook.yes_buy_orders,
-                (true, false) => &mut orderbook.yes_sell_orders,
-                (false, true) => &mut orderbook.no_buy_orders,
-                (false, false) => &mut orderbook.no_sell_orders,
-            };
+        position.owner = ctx.accounts.owner.key();
+        position.market = market.key();
+
+        // Settle the immediately matched portion at the makers' prices
+        if is_bid {
+            position.add_shares(answer_index, is_yes, match_result.filled_amount);
+            position.add_cost_basis(answer_index, match_result.notional, &mut market.refund_basis[answer_index as usize]);
+            market.deposit_collateral(answer_index, match_result.set_notional + match_result.maker_set_notional);
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner,
+                match_result.direct_notional(),
+            )?;
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_ata,
+                &ctx.accounts.owner,
+                match_result.set_notional,
+            )?;
+        } else {
+            position.remove_shares(answer_index, is_yes, match_result.filled_amount)?;
+            position.reduce_cost_basis(answer_index, match_result.notional, &mut market.refund_basis[answer_index as usize]);
+            market.withdraw_collateral(answer_index, match_result.set_notional + match_result.maker_set_notional);
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                match_result.direct_notional(),
+            )?;
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.vault_ata,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                match_result.set_notional,
+            )?;
+        }
+        transfer_maker_set_collateral(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.vault_ata,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            &match_result,
+            is_bid,
+        )?;
+        market.volume = market.volume.checked_add(match_result.notional).unwrap();
 
-            require!(bucket.len() < 100, LikeliError::OrderbookFull);
-            bucket.push(order_key);
+        // Escrow the resting remainder: collateral for bids, shares for asks.
+        // Remainders below the minimum order size are dropped rather than rested
+        let resting_qty = if order_type.rests() && match_result.remaining_amount >= market.min_order_qty {
+            match_result.remaining_amount
+        } else {
+            0
+        };
+        if is_bid {
+            let escrow = order_notional(price, resting_qty);
+            require!(escrow > 0 || resting_qty == 0, LikeliError::InvalidAmount);
+            transfer_to_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.owner_ata,
+                &ctx.accounts.order_vault,
+                &ctx.accounts.owner,
+                escrow,
+            )?;
+        } else {
+            position.lock_shares(answer_index, is_yes, resting_qty)?;
         }
 
-        msg!("Multi-choice order placed (matched {}): {}", match_result.filled_amount, order.key());
+        // Only add to book if something is left to rest
+        if resting_qty > 0 {
+            let expires_at = expires_in.map_or(0, |ei| clock.unix_timestamp + ei);
+            let order_id = orderbook.rest_order(
+                &ctx.accounts.owner.key(),
+                is_yes,
+                is_bid,
+                price,
+                resting_qty,
+                expires_at,
+            )?;
+            msg!("Multi-choice order placed (matched {}): {}", match_result.filled_amount, order_id);
+        } else {
+            msg!("Multi-choice order matched {} of {}", match_result.filled_amount, qty);
+        }
         Ok(())
     }
     
-    /// Cancel an order
-    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
-        let orderbook = &mut ctx.accounts.orderbook;
-        let order = &ctx.accounts.order;
-        let order_pubkey = order.key();
-        
-        let removed = remove_order_from_book(orderbook, order_pubkey, order.is_yes, order.is_bid)?;
-        require!(removed, LikeliError::OrderNotFound);
+    /// Cancel a resting order, releasing the escrow held for its remainder
+    /// and settling the owner's other fills in the same book
+    pub fn cancel_order(
+        ctx: Context<CancelOrder>,
+        is_yes: bool,
+        is_bid: bool,
+        order_id: u64,
+    ) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        let owner = ctx.accounts.owner.key();
+
+        orderbook.cancel_order(&owner, is_yes, is_bid, order_id)?;
+
+        let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+        let payout = with_refund_basis(&ctx.accounts.market, answer_index, |refund_basis| {
+            settle_owner_balance(
+                &mut orderbook,
+                &owner,
+                ctx.accounts.user_position.as_deref_mut(),
+                ctx.accounts.multi_position.as_deref_mut(),
+                refund_basis,
+            )
+        })?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.owner_ata,
+            &ctx.accounts.vault_authority,
+            &ctx.accounts.market.key(),
+            ctx.bumps.vault_authority,
+            payout,
+        )?;
         
-        msg!("Order cancelled: {}", order_pubkey);
+        msg!("Order cancelled: {}", order_id);
         Ok(())
     }
 
-    // ============== UTILITY INSTRUCTIONS ==============
+    /// Cancel all of the caller's resting orders in an orderbook and settle
+    /// their balance there. Pass the market's other orderbooks (the remaining
+    /// answers of a multi-choice market) as writable remaining accounts to
+    /// cancel across the whole market in one instruction.
+    pub fn cancel_all_orders<'info>(ctx: Context<'_, '_, 'info, 'info, CancelOrder<'info>>) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        let market_key = ctx.accounts.market.key();
+        let other_books = ctx.remaining_accounts
+            .iter()
+            .map(AccountLoader::<Orderbook>::try_from)
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut cancelled = 0;
+        let mut payout: u64 = 0;
+        for loader in std::iter::once(&ctx.accounts.orderbook).chain(other_books.iter()) {
+            let mut orderbook = loader.load_mut()?;
+            require_keys_eq!(orderbook.market, market_key, LikeliError::OrderbookMismatch);
+
+            cancelled += orderbook.cancel_all(&owner);
+            if orderbook.find_owner(&owner).is_some_and(|slot| orderbook.balances[slot].has_unsettled()) {
+                let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+                let settled = with_refund_basis(&ctx.accounts.market, answer_index, |refund_basis| {
+                    settle_owner_balance(
+                        &mut orderbook,
+                        &owner,
+                        ctx.accounts.user_position.as_deref_mut(),
+                        ctx.accounts.multi_position.as_deref_mut(),
+                        refund_basis,
+                    )
+                })?;
+                payout = payout.checked_add(settled).unwrap();
+            }
+        }
+        require!(cancelled > 0, LikeliError::OrderNotFound);
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.owner_ata,
+            &ctx.accounts.vault_authority,
+            &market_key,
+            ctx.bumps.vault_authority,
+            payout,
+        )?;
 
-    /// Set fees for a market
-    pub fn set_market_fees(
-        ctx: Context<SetMarketFees>,
-        fee_bps: u16,
-        creator_fee_bps: u16,
-        platform_fee_bps: u16,
-        liquidity_fee_bps: u16,
-    ) -> Result<()> {
-        let market = &mut ctx.accounts.market;
-        
-        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
-        
-        let total_fees = fee_bps as u32 + creator_fee_bps as u32 + platform_fee_bps as u32 + liquidity_fee_bps as u32;
-        require!(total_fees <= 1000, LikeliError::FeesTooHigh);
-        
-        market.fee_bps = fee_bps;
-        market.creator_fee_bps = creator_fee_bps;
-        market.platform_fee_bps = platform_fee_bps;
-        market.liquidity_fee_bps = liquidity_fee_bps;
-        
-        msg!("Fees updated: {}bps total", market.fee_bps);
+        msg!("Cancelled {} orders for {} in market {}", cancelled, owner, market_key);
         Ok(())
     }
 
-    /// Get market price info
-    pub fn get_market_price(ctx: Context<GetMarketPrice>) -> Result<()> {
-        let market = &ctx.accounts.market;
-        
-        let total_pool = market.yes_pool.checked_add(market.no_pool).unwrap();
-        let yes_prob = (market.no_pool as u128)
-            .checked_mul(10000).unwrap()
-            .checked_div(total_pool as u128).unwrap() as u64;
+    /// Amend a resting order's price and size in place. Reducing size at the
+    /// same price keeps its time priority; any other change re-queues it
+    /// under a new order id. Amended orders never take liquidity.
+    pub fn amend_order(
+        ctx: Context<CancelOrder>,
+        is_yes: bool,
+        is_bid: bool,
+        order_id: u64,
+        new_price: u64,
+        new_qty: u64,
+    ) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        let owner = ctx.accounts.owner.key();
+        let clock = Clock::get()?;
 
-        msg!(
-            "Market: {} | YES: {}% | NO: {}% | Volume: {}",
-            market.question,
-            yes_prob / 100,
-            100 - (yes_prob / 100),
-            market.total_volume
-        );
+        require!(new_qty > 0, LikeliError::InvalidAmount);
+        require!(new_price > 0 && new_price < 10000, LikeliError::InvalidPrice);
+        let rules = market_order_rules(&ctx.accounts.market, orderbook.is_multi != 0)?;
+        require!(clock.unix_timestamp < rules.close_time, LikeliError::TradingClosed);
+        check_order_rules(new_price, new_qty, rules.tick_size, rules.min_order_qty)?;
 
+        // A re-priced order must still rest without crossing
+        let taker = TakerOrder {
+            owner,
+            is_yes,
+            is_buy: is_bid,
+            limit_price: new_price,
+            amount: new_qty,
+            amount_is_collateral: false,
+            self_trade: SelfTradeMode::CancelTaker,
+        };
+        let best = orderbook.best_source(&taker, clock.unix_timestamp);
+        require!(!best.is_some_and(|(_, price)| taker.crosses(price)), LikeliError::AmendWouldCross);
+
+        let (old, new_id) = orderbook.amend_order(&owner, is_yes, is_bid, order_id, new_price, new_qty)?;
+        let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+        drop(orderbook);
+
+        // Top up or release escrow: collateral for bids, locked shares for asks
+        if is_bid {
+            let old_escrow = order_notional(old.price as u64, old.qty);
+            let new_escrow = order_notional(new_price, new_qty);
+            require!(new_escrow > 0, LikeliError::InvalidAmount);
+            if new_escrow > old_escrow {
+                transfer_to_vault(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.owner_ata,
+                    &ctx.accounts.order_vault,
+                    &ctx.accounts.owner,
+                    new_escrow - old_escrow,
+                )?;
+            } else {
+                transfer_from_vault(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.order_vault,
+                    &ctx.accounts.owner_ata,
+                    &ctx.accounts.vault_authority,
+                    &ctx.accounts.market.key(),
+                    ctx.bumps.vault_authority,
+                    old_escrow - new_escrow,
+                )?;
+            }
+        } else if new_qty != old.qty {
+            match answer_index {
+                None => {
+                    let position = ctx.accounts.user_position.as_mut().ok_or(LikeliError::MissingPosition)?;
+                    if new_qty > old.qty {
+                        position.lock_shares(is_yes, new_qty - old.qty)?;
+                    } else {
+                        position.unlock_shares(is_yes, old.qty - new_qty);
+                    }
+                }
+                Some(index) => {
+                    let position = ctx.accounts.multi_position.as_mut().ok_or(LikeliError::MissingPosition)?;
+                    if new_qty > old.qty {
+                        position.lock_shares(index, is_yes, new_qty - old.qty)?;
+                    } else {
+                        position.unlock_shares(index, is_yes, old.qty - new_qty);
+                    }
+                }
+            }
+        }
+
+        msg!("Order {} amended to {} @ {}bps: {}", order_id, new_qty, new_price, new_id);
         Ok(())
     }
-}
 
-// ============== HELPER FUNCTIONS ==============
+    /// Settle the caller's fills in an orderbook: bids receive shares, asks
+    /// receive the collateral takers paid at the order's price
+    pub fn settle_funds(ctx: Context<CancelOrder>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        let owner = ctx.accounts.owner.key();
+
+        let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+        let payout = with_refund_basis(&ctx.accounts.market, answer_index, |refund_basis| {
+            settle_owner_balance(
+                &mut orderbook,
+                &owner,
+                ctx.accounts.user_position.as_deref_mut(),
+                ctx.accounts.multi_position.as_deref_mut(),
+                refund_basis,
+            )
+        })?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &ctx.accounts.owner_ata,
+            &ctx.accounts.vault_authority,
+            &ctx.accounts.market.key(),
+            ctx.bumps.vault_authority,
+            payout,
+        )?;
 
-fn calculate_fee(amount: u64, fee_bps: u16) -> u64 {
-    if fee_bps == 0 {
-        return 0;
+        msg!("Settled {} collateral for {} in orderbook {}", payout, owner, ctx.accounts.orderbook.key());
+        Ok(())
     }
-    (amount as u128 * fee_bps as u128 / 10000) as u64
-}
 
-fn calculate_shares_out(yes_pool: u64, no_pool: u64, amount: u64, is_yes: bool) -> u64 {
-    let y = yes_pool as u128;
-    let n = no_pool as u128;
-    let a = amount as u128;
+    /// Evict expired orders from an orderbook. Permissionless crank: escrow is
+    /// released into each owner's orderbook balance. Owners passed as
+    /// remaining account pairs `[position, token account]` are settled
+    /// directly and their slots freed; the rest claim with `settle_funds`.
+    pub fn evict_expired_orders<'info>(ctx: Context<'_, '_, 'info, 'info, EvictOrders<'info>>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        let clock = Clock::get()?;
 
-    if is_yes {
-        // Buy YES with amount A:
-        // New N' = N + A. Shares obtained: A * (1 + Y / (N + A))
-        (a + (a * y / (n + a).max(1))) as u64
-    } else {
-        // Buy NO with amount A:
-        // New Y' = Y + A. Shares obtained: A * (1 + N / (Y + A))
-        (a + (a * n / (y + a).max(1))) as u64
-    }
-}
+        let evicted = orderbook.evict_expired(clock.unix_timestamp);
+        require!(evicted > 0 || !ctx.remaining_accounts.is_empty(), LikeliError::NoExpiredOrders);
+
+        let owner_accounts = ctx.remaining_accounts.chunks_exact(2);
+        require!(owner_accounts.remainder().is_empty(), LikeliError::MissingPosition);
+        let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+        with_refund_basis(&ctx.accounts.market, answer_index, |refund_basis| {
+            for pair in owner_accounts {
+                let owner = Account::<TokenAccount>::try_from(&pair[1])?.owner;
+                let (owner_ata, payout) = settle_for_owner(
+                    &mut orderbook,
+                    &owner,
+                    &pair[0],
+                    &pair[1],
+                    &ctx.accounts.order_vault.mint,
+                    refund_basis,
+                )?;
+                transfer_from_vault(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.order_vault,
+                    &owner_ata,
+                    &ctx.accounts.vault_authority,
+                    &ctx.accounts.market.key(),
+                    ctx.bumps.vault_authority,
+                    payout,
+                )?;
+            }
+            Ok(())
+        })?;
 
-fn sync_sibling_pools<'info>(
-    current_answer_key: Pubkey,
-    new_price: u64, // bps
-    market_key: Pubkey,
-    expected_sibling_count: u8,
-    remaining_accounts: &[AccountInfo<'info>],
-) -> Result<()> {
-    // Security: Validate that enough sibling accounts are passed
-    require!(
-        remaining_accounts.len() >= expected_sibling_count as usize,
-        LikeliError::MissingSiblings
-    );
+        msg!("Evicted {} expired orders from orderbook {}", evicted, ctx.accounts.orderbook.key());
+        Ok(())
+    }
 
-    msg!("Syncing siblings for answer {}. New price: {}bps. Siblings passed: {}", current_answer_key, new_price, remaining_accounts.len());
-    let mut other_answers = Vec::new();
-    let mut others_old_prob_sum: u128 = 0;
+    /// Evict the owner with the least collateral resting in a full orderbook,
+    /// so idle or dust orders can't keep new makers out. Permissionless: the
+    /// owner's orders are cancelled and their balance settled straight to
+    /// them; pass their position and token account as remaining accounts.
+    /// Send it in the same transaction as the order that needs the slot.
+    pub fn evict_book_owner<'info>(ctx: Context<'_, '_, 'info, 'info, EvictOrders<'info>>) -> Result<()> {
+        let mut orderbook = ctx.accounts.orderbook.load_mut()?;
+        require!(orderbook.find_owner(&Pubkey::default()).is_none(), LikeliError::BookOwnersAvailable);
+        let [position, owner_ata] = ctx.remaining_accounts else {
+            return err!(LikeliError::MissingPosition);
+        };
 
-    for info in remaining_accounts {
-        if info.key() == current_answer_key {
-            continue;
-        }
-        if info.owner != &crate::ID {
-            continue;
-        }
+        let slot = orderbook.smallest_owner().ok_or(LikeliError::OrderNotFound)?;
+        let owner = orderbook.balances[slot].owner;
+        let cancelled = orderbook.cancel_all(&owner);
+
+        let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+        let (owner_ata, payout) = with_refund_basis(&ctx.accounts.market, answer_index, |refund_basis| {
+            settle_for_owner(
+                &mut orderbook,
+                &owner,
+                position,
+                owner_ata,
+                &ctx.accounts.order_vault.mint,
+                refund_basis,
+            )
+        })?;
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.order_vault,
+            &owner_ata,
+            &ctx.accounts.vault_authority,
+            &ctx.accounts.market.key(),
+            ctx.bumps.vault_authority,
+            payout,
+        )?;
 
-        let mut data: &[u8] = &info.try_borrow_data()?;
-        if let Ok(sibling) = Answer::try_deserialize(&mut data) {
-            if sibling.market == market_key {
-                let total = sibling.yes_pool.checked_add(sibling.no_pool).unwrap();
-                if total > 0 {
-                    let p = (sibling.no_pool as u128 * 10000 / total as u128);
-                    others_old_prob_sum += p;
-                    other_answers.push((info, sibling, total, p));
-                }
-            }
-        }
+        msg!("Evicted {} from orderbook {}: {} orders cancelled", owner, ctx.accounts.orderbook.key(), cancelled);
+        Ok(())
     }
 
-    if other_answers.is_empty() {
-        return Ok(());
+    // ============== OPTIMISTIC ORACLE ==============
+
+    /// Set the proposal bond and challenge window for a binary market
+    pub fn set_oracle_config(
+        ctx: Context<SetMarketFees>,
+        oracle_bond: u64,
+        challenge_window: i64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(challenge_window > 0, LikeliError::InvalidChallengeWindow);
+        require!(oracle_bond > 0, LikeliError::OracleBondRequired);
+
+        market.oracle_bond = oracle_bond;
+        market.challenge_window = challenge_window;
+
+        msg!("Oracle config updated: bond {}, window {}s", oracle_bond, challenge_window);
+        Ok(())
     }
 
-    let target_others_prob_sum = 10000u32.saturating_sub(new_price as u32) as u128;
+    /// Set the proposal bond and challenge window for a multi-choice market
+    pub fn set_multi_oracle_config(
+        ctx: Context<SetMultiMarketConfig>,
+        oracle_bond: u64,
+        challenge_window: i64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
 
-    // Track actual sum for rounding error compensation
-    let mut actual_prob_sum: u128 = 0;
-    let mut last_sibling_info: Option<AccountInfo> = None;
-    let mut last_sibling: Option<Answer> = None;
-    let mut last_total: u64 = 0;
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(challenge_window > 0, LikeliError::InvalidChallengeWindow);
+        require!(oracle_bond > 0, LikeliError::OracleBondRequired);
 
-    for (info, mut sibling, total, old_p) in other_answers {
-        let new_p = if others_old_prob_sum > 0 {
-            old_p.checked_mul(target_others_prob_sum).unwrap()
-                .checked_div(others_old_prob_sum).unwrap()
-        } else {
-            target_others_prob_sum.checked_div(1).unwrap()
-        };
+        market.oracle_bond = oracle_bond;
+        market.challenge_window = challenge_window;
 
-        sibling.no_pool = (total as u128 * new_p / 10000) as u64;
-        sibling.yes_pool = total.checked_sub(sibling.no_pool).unwrap();
-        
-        // Track probability for rounding compensation
-        let sibling_total = sibling.yes_pool.checked_add(sibling.no_pool).unwrap() as u128;
-        if sibling_total > 0 {
-            actual_prob_sum += sibling.no_pool as u128 * 10000 / sibling_total;
+        msg!("Multi-market oracle config updated: bond {}, window {}s", oracle_bond, challenge_window);
+        Ok(())
+    }
+
+    /// Propose how a binary market resolved, posting the market's bond.
+    /// Once the resolution time has passed the resolver may propose, and
+    /// anyone may after `RESOLVER_EXCLUSIVE_WINDOW`; the proposal finalizes
+    /// unless disputed within the challenge window. Needs a bond to be set.
+    pub fn propose_outcome(ctx: Context<ProposeOutcome>, outcome: MarketOutcome) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(market.oracle_bond > 0, LikeliError::OracleBondRequired);
+        require!(clock.unix_timestamp >= market.resolution_time, LikeliError::TooEarlyToResolve);
+        require!(
+            ctx.accounts.proposer.key() == market.resolver
+                || clock.unix_timestamp >= market.resolution_time + RESOLVER_EXCLUSIVE_WINDOW,
+            LikeliError::ResolverWindowOpen
+        );
+        require!(market.price_feed == Pubkey::default(), LikeliError::PriceFeedMarket);
+        if let MarketOutcome::Probability(bps) = outcome {
+            require!(bps <= 10000, LikeliError::InvalidProbability);
         }
-        
-        // Store last sibling for rounding adjustment
-        last_sibling_info = Some(info.clone());
-        last_sibling = Some(sibling.clone());
-        last_total = total;
-        
-        let mut data = info.try_borrow_mut_data()?;
-        sibling.try_serialize(&mut *data)?;
+
+        ctx.accounts.proposal.set_inner(Proposal {
+            market: market.key(),
+            target: market.key(),
+            proposer: ctx.accounts.proposer.key(),
+            outcome,
+            bond: market.oracle_bond,
+            challenge_ends: clock.unix_timestamp + market.challenge_window,
+            disputer: None,
+            bump: ctx.bumps.proposal,
+            members: Vec::new(),
+            threshold: 0,
+            votes: [None; MAX_COMMITTEE_MEMBERS],
+            ruling: None,
+            voting_ends: 0,
+        });
+        market.proposal_pending = true;
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.proposer_ata,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.proposer,
+            market.oracle_bond,
+        )?;
+
+        msg!("Proposed {:?} for market {}, challenge window ends {}", outcome, market.key(), ctx.accounts.proposal.challenge_ends);
+        Ok(())
     }
 
-    // Fix 1: Rounding error compensation - adjust last sibling to ensure sum = 100%
-    if let (Some(info), Some(mut sibling)) = (last_sibling_info, last_sibling) {
-        let rounding_error = target_others_prob_sum as i128 - actual_prob_sum as i128;
-        if rounding_error.abs() > 0 && rounding_error.abs() < 100 {
-            // Adjust no_pool by the rounding error
-            let adjustment = (last_total as i128 * rounding_error / 10000) as i64;
-            sibling.no_pool = (sibling.no_pool as i64 + adjustment).max(0) as u64;
-            sibling.yes_pool = last_total.saturating_sub(sibling.no_pool);
-            
-            let mut data = info.try_borrow_mut_data()?;
-            sibling.try_serialize(&mut *data)?;
+    /// Propose how an answer of a multi-choice market resolved, posting the market's bond.
+    /// Open to the resolver first, like `propose_outcome`
+    pub fn propose_answer_outcome(ctx: Context<ProposeAnswerOutcome>, outcome: MarketOutcome) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let answer = &mut ctx.accounts.answer;
+        let clock = Clock::get()?;
+
+        require!(!answer.resolved, LikeliError::AnswerAlreadyResolved);
+        require!(market.oracle_bond > 0, LikeliError::OracleBondRequired);
+        require!(clock.unix_timestamp >= market.resolution_time, LikeliError::TooEarlyToResolve);
+        require!(
+            ctx.accounts.proposer.key() == market.resolver
+                || clock.unix_timestamp >= market.resolution_time + RESOLVER_EXCLUSIVE_WINDOW,
+            LikeliError::ResolverWindowOpen
+        );
+        require!(!matches!(outcome, MarketOutcome::Probability(_)), LikeliError::InvalidProbability);
+
+        ctx.accounts.proposal.set_inner(Proposal {
+            market: market.key(),
+            target: answer.key(),
+            proposer: ctx.accounts.proposer.key(),
+            outcome,
+            bond: market.oracle_bond,
+            challenge_ends: clock.unix_timestamp + market.challenge_window,
+            disputer: None,
+            bump: ctx.bumps.proposal,
+            members: Vec::new(),
+            threshold: 0,
+            votes: [None; MAX_COMMITTEE_MEMBERS],
+            ruling: None,
+            voting_ends: 0,
+        });
+        answer.proposal_pending = true;
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.proposer_ata,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.proposer,
+            market.oracle_bond,
+        )?;
+
+        msg!("Proposed {:?} for answer {}, challenge window ends {}", outcome, answer.index, ctx.accounts.proposal.challenge_ends);
+        Ok(())
+    }
+
+    /// Set the global dispute committee, used by markets without their own.
+    /// Only the program's upgrade authority can change it.
+    pub fn set_global_committee(
+        ctx: Context<SetGlobalCommittee>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_committee(&members, threshold)?;
+
+        let committee = &mut ctx.accounts.committee;
+        committee.members = members;
+        committee.threshold = threshold;
+        committee.bump = ctx.bumps.committee;
+
+        msg!("Global committee set: {}-of-{}", threshold, committee.members.len());
+        Ok(())
+    }
+
+    /// Give a binary market its own dispute committee. Must be set before the
+    /// resolution time so it cannot change under an open dispute.
+    pub fn set_market_committee(
+        ctx: Context<SetMarketCommittee>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(clock.unix_timestamp < market.resolution_time, LikeliError::CommitteeLocked);
+        validate_committee(&members, threshold)?;
+
+        let committee = &mut ctx.accounts.committee;
+        committee.members = members;
+        committee.threshold = threshold;
+        committee.bump = ctx.bumps.committee;
+        market.committee = committee.key();
+
+        msg!("Market committee set: {}-of-{}", threshold, committee.members.len());
+        Ok(())
+    }
+
+    /// Give a multi-choice market its own dispute committee
+    pub fn set_multi_market_committee(
+        ctx: Context<SetMultiMarketCommittee>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(clock.unix_timestamp < market.resolution_time, LikeliError::CommitteeLocked);
+        validate_committee(&members, threshold)?;
+
+        let committee = &mut ctx.accounts.committee;
+        committee.members = members;
+        committee.threshold = threshold;
+        committee.bump = ctx.bumps.committee;
+        market.committee = committee.key();
+
+        msg!("Multi-market committee set: {}-of-{}", threshold, committee.members.len());
+        Ok(())
+    }
+
+    /// Dispute a proposal within its challenge window by matching its bond.
+    /// The dispute escalates to the market's committee, or the global one,
+    /// whose members as of now vote on it. Without a ruling within
+    /// `DISPUTE_VOTING_PERIOD` the proposal lapses to N/A.
+    pub fn dispute_proposal(ctx: Context<DisputeProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let clock = Clock::get()?;
+
+        require!(proposal.disputer.is_none(), LikeliError::ProposalDisputed);
+        require!(clock.unix_timestamp < proposal.challenge_ends, LikeliError::ChallengeWindowClosed);
+
+        let expected = match market_committee(&ctx.accounts.market, proposal.target == proposal.market)? {
+            committee if committee != Pubkey::default() => committee,
+            _ => Pubkey::find_program_address(&[COMMITTEE_SEED], &crate::ID).0,
+        };
+        require_keys_eq!(ctx.accounts.committee.key(), expected, LikeliError::CommitteeMismatch);
+        // The global committee may not have been created yet
+        require!(ctx.accounts.committee.owner == &crate::ID, LikeliError::NoCommittee);
+        let committee = Committee::try_deserialize(&mut &ctx.accounts.committee.try_borrow_data()?[..])?;
+
+        proposal.disputer = Some(ctx.accounts.disputer.key());
+        proposal.voting_ends = clock.unix_timestamp + DISPUTE_VOTING_PERIOD;
+        proposal.members = committee.members;
+        proposal.threshold = committee.threshold;
+
+        transfer_to_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.disputer_ata,
+            &ctx.accounts.bond_vault,
+            &ctx.accounts.disputer,
+            proposal.bond,
+        )?;
+
+        msg!("Proposal for {} disputed by {}", proposal.target, ctx.accounts.disputer.key());
+        Ok(())
+    }
+
+    /// Vote on a disputed proposal as a member of its committee when it was
+    /// disputed. The first outcome to reach the threshold becomes the ruling.
+    pub fn vote_on_dispute(ctx: Context<VoteOnDispute>, outcome: MarketOutcome) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+
+        require!(proposal.disputer.is_some(), LikeliError::ProposalNotDisputed);
+        require!(proposal.ruling.is_none(), LikeliError::RulingReached);
+        require!(Clock::get()?.unix_timestamp < proposal.voting_ends, LikeliError::VotingClosed);
+        if let MarketOutcome::Probability(bps) = outcome {
+            // Answers never resolve to a probability
+            require!(proposal.target == proposal.market && bps <= 10000, LikeliError::InvalidProbability);
+        }
+
+        let member = proposal.members
+            .iter()
+            .position(|m| *m == ctx.accounts.member.key())
+            .ok_or(LikeliError::NotCommitteeMember)?;
+        require!(proposal.votes[member].is_none(), LikeliError::AlreadyVoted);
+        proposal.votes[member] = Some(outcome);
+
+        let support = proposal.votes.iter().filter(|v| **v == Some(outcome)).count();
+        if support >= proposal.threshold as usize {
+            proposal.ruling = Some(outcome);
+            msg!("Committee ruled {:?} for {}", outcome, proposal.target);
+        } else {
+            msg!("Vote {:?} recorded for {}: {}/{}", outcome, proposal.target, support, proposal.threshold);
         }
+        Ok(())
     }
 
-    Ok(())
-}
+    /// Finalize a binary market's proposal. Undisputed proposals finalize
+    /// once the challenge window ends, disputed ones once ruled on. Bonds go
+    /// to whichever side the outcome favours, or back to their owners if the
+    /// dispute lapsed to N/A or the market was cancelled as abandoned.
+    pub fn finalize_proposal(ctx: Context<FinalizeProposal>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let proposal = &ctx.accounts.proposal;
+
+        market.proposal_pending = false;
+        if market.abandoned {
+            return refund_bonds(
+                &ctx.accounts.token_program,
+                &ctx.accounts.bond_vault,
+                &ctx.accounts.proposer_ata,
+                ctx.accounts.disputer_ata.as_ref(),
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                proposal,
+            );
+        }
 
-fn remove_order_from_book(
-    orderbook: &mut Orderbook,
-    order_pubkey: Pubkey,
-    is_yes: bool,
-    is_bid: bool,
-) -> Result<bool> {
-    let order_list = match (is_yes, is_bid) {
-        (true, true) => &mut orderbook.yes_buy_orders,
-        (true, false) => &mut orderbook.yes_sell_orders,
-        (false, true) => &mut orderbook.no_buy_orders,
-        (false, false) => &mut orderbook.no_sell_orders,
-    };
-    
-    if let Some(pos) = order_list.iter().position(|&k| k == order_pubkey) {
-        order_list.remove(pos);
-        Ok(true)
-    } else {
-        Ok(false)
+        let outcome = proposal_ruling(proposal)?;
+        apply_market_resolution(market, outcome)?;
+        if proposal.lapsed() {
+            return refund_bonds(
+                &ctx.accounts.token_program,
+                &ctx.accounts.bond_vault,
+                &ctx.accounts.proposer_ata,
+                ctx.accounts.disputer_ata.as_ref(),
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                proposal,
+            );
+        }
+
+        let recipient = if outcome == proposal.outcome {
+            &ctx.accounts.proposer_ata
+        } else {
+            ctx.accounts.disputer_ata.as_ref().ok_or(LikeliError::MissingDisputerAccount)?
+        };
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.bond_vault,
+            recipient,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            proposal.total_bond(),
+        )
     }
-}
 
-fn has_winner(_market: &MultiMarket) -> bool {
-    // Simplified - in production check if any answer resolved YES
-    false
-}
+    /// Finalize an answer's proposal. An answer already resolved NO or N/A by
+    /// a sibling's resolution settles against that outcome; an undisputed
+    /// proposal that loses that way forfeits its bond to the market vault.
+    /// Answers cancelled as abandoned and lapsed disputes return both bonds;
+    /// a lapse that can't cancel a partly resolved one-winner market leaves
+    /// the answer open for a new proposal.
+    pub fn finalize_answer_proposal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FinalizeAnswerProposal<'info>>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let answer = &mut ctx.accounts.answer;
+        let proposal = &ctx.accounts.proposal;
+
+        answer.proposal_pending = false;
+        if market.abandoned && answer.outcome == Some(MarketOutcome::Invalid) {
+            return refund_bonds(
+                &ctx.accounts.token_program,
+                &ctx.accounts.bond_vault,
+                &ctx.accounts.proposer_ata,
+                ctx.accounts.disputer_ata.as_ref(),
+                &ctx.accounts.vault_authority,
+                &market.key(),
+                ctx.bumps.vault_authority,
+                proposal,
+            );
+        }
 
-/// Result of order matching attempt
-#[derive(Clone, Copy, Debug)]
-pub struct MatchResult {
-    pub filled_amount: u64,
-    pub remaining_amount: u64,
-    pub matched_price: u64,
-}
+        let outcome = match answer.outcome {
+            Some(outcome) => outcome,
+            None => {
+                let outcome = proposal_ruling(proposal)?;
+                let lapsed = proposal.lapsed();
+                if !(lapsed && market.is_one_winner && market.answers_resolved > 0) {
+                    apply_answer_resolution(market, answer, outcome, ctx.remaining_accounts)?;
+                }
+                if lapsed {
+                    return refund_bonds(
+                        &ctx.accounts.token_program,
+                        &ctx.accounts.bond_vault,
+                        &ctx.accounts.proposer_ata,
+                        ctx.accounts.disputer_ata.as_ref(),
+                        &ctx.accounts.vault_authority,
+                        &market.key(),
+                        ctx.bumps.vault_authority,
+                        proposal,
+                    );
+                }
+                outcome
+            }
+        };
 
-/// Find matching orders in the orderbook
-/// Returns the amount that can be filled at the limit price
-/// 
-/// Matching logic:
-/// - Buy orders match against sell orders at price <= buy_price
-/// - Sell orders match against buy orders at price >= sell_price
-/// - Orders are matched in price-time priority
-fn find_matching_orders<'info>(
-    orderbook: &Orderbook,
-    opposing_accounts: &[AccountInfo<'info>],
-    answer_index: Option<u8>,
-    is_yes: bool,
-    is_buy: bool,
-    limit_price: u64,
-    amount: u64,
-) -> Result<MatchResult> {
-    let mut filled_amount = 0;
-    let mut remaining_amount = amount;
-    
-    for account_info in opposing_accounts {
-        if remaining_amount == 0 { break; }
-        
-        if account_info.owner != &crate::ID { continue; }
-        let mut order_data = account_info.try_borrow_mut_data()?;
-        if order_data.len() < 8 { continue; }
-        
-        let mut data_ptr: &[u8] = &order_data;
-        let mut order = if let Ok(o) = LimitOrder::try_deserialize(&mut data_ptr) {
-            o
+        let recipient = if outcome == proposal.outcome {
+            &ctx.accounts.proposer_ata
+        } else if proposal.disputer.is_some() {
+            ctx.accounts.disputer_ata.as_ref().ok_or(LikeliError::MissingDisputerAccount)?
         } else {
-            continue;
+            &ctx.accounts.vault_ata
         };
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.bond_vault,
+            recipient,
+            &ctx.accounts.vault_authority,
+            &market.key(),
+            ctx.bumps.vault_authority,
+            proposal.total_bond(),
+        )
+    }
+
+    // ============== PRICE FEED RESOLUTION ==============
+
+    /// Make a binary market resolve from a Pyth-style price account: YES if
+    /// the price at the resolution time is above `strike_price × 10^strike_expo`.
+    /// The account must be owned by `price_feed_owner`, the oracle's program.
+    /// Only possible before any trading, so traders always see the final rule.
+    pub fn set_price_feed(
+        ctx: Context<SetMarketFees>,
+        price_feed: Pubkey,
+        price_feed_owner: Pubkey,
+        strike_price: i64,
+        strike_expo: i32,
+        max_staleness: i64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.total_volume == 0, LikeliError::MarketHasVolume);
+        require!(!market.proposal_pending, LikeliError::ProposalPending);
+        require!(max_staleness > 0, LikeliError::InvalidPriceFeed);
+        require!(price_feed_owner != Pubkey::default(), LikeliError::InvalidPriceFeed);
+
+        market.price_feed = price_feed;
+        market.price_feed_owner = price_feed_owner;
+        market.strike_price = strike_price;
+        market.strike_expo = strike_expo;
+        market.max_staleness = max_staleness;
+
+        msg!("Market {} resolves from {} against strike {}e{}", market.key(), price_feed, strike_price, strike_expo);
+        Ok(())
+    }
+
+    /// Resolve a price-feed market. Permissionless: anyone can crank it with a
+    /// price published at the resolution time or up to `max_staleness` after it.
+    pub fn resolve_from_price_feed(ctx: Context<ResolveFromPriceFeed>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(clock.unix_timestamp >= market.resolution_time, LikeliError::TooEarlyToResolve);
+
+        let reading = read_price_feed(&ctx.accounts.price_feed.try_borrow_data()?)?;
+        // Prices from before the resolution time don't answer the question
+        require!(
+            reading.publish_time >= market.resolution_time
+                && reading.publish_time - market.resolution_time <= market.max_staleness,
+            LikeliError::StalePrice
+        );
+
+        let above = compare_prices(reading.price, reading.expo, market.strike_price, market.strike_expo).is_gt();
+        msg!("Price {}e{} (conf {}) at {}", reading.price, reading.expo, reading.conf, reading.publish_time);
+        apply_market_resolution(market, if above { MarketOutcome::Yes } else { MarketOutcome::No })
+    }
+
+    // ============== ABANDONED MARKETS ==============
+
+    /// Set how long after the resolution time a binary market may go
+    /// unresolved before anyone can cancel it. Only possible before any trading.
+    pub fn set_resolution_grace(ctx: Context<SetMarketFees>, resolution_grace: i64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.total_volume == 0, LikeliError::MarketHasVolume);
+        require!(resolution_grace > 0, LikeliError::InvalidResolutionGrace);
+
+        market.resolution_grace = resolution_grace;
+
+        msg!("Resolution grace for {} set to {}s", market.key(), resolution_grace);
+        Ok(())
+    }
+
+    /// Set the resolution grace period of a multi-choice market. Only possible before any trading.
+    pub fn set_multi_resolution_grace(ctx: Context<SetMultiMarketConfig>, resolution_grace: i64) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.volume == 0, LikeliError::MarketHasVolume);
+        require!(resolution_grace > 0, LikeliError::InvalidResolutionGrace);
+
+        market.resolution_grace = resolution_grace;
+
+        msg!("Resolution grace for {} set to {}s", market.key(), resolution_grace);
+        Ok(())
+    }
+
+    /// Cancel a binary market left unresolved past its grace period.
+    /// Permissionless; holders reclaim their cost basis with the claim instructions.
+    pub fn cancel_abandoned_market(ctx: Context<CancelAbandonedMarket>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(
+            clock.unix_timestamp >= market.resolution_time.saturating_add(market.resolution_grace),
+            LikeliError::GracePeriodActive
+        );
+
+        market.abandoned = true;
+        apply_market_resolution(market, MarketOutcome::Invalid)
+    }
+
+    /// Cancel the unresolved answers of a multi-choice market left past its
+    /// grace period, passed as writable remaining accounts. Answers already
+    /// resolved keep their outcome, except in one-winner markets: their
+    /// answers share collateral, so pass every answer to cancel them all.
+    pub fn cancel_abandoned_multi_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelAbandonedMultiMarket<'info>>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let clock = Clock::get()?;
+
+        require!(!market.resolved, LikeliError::MarketResolved);
+        require!(
+            clock.unix_timestamp >= market.resolution_time.saturating_add(market.resolution_grace),
+            LikeliError::GracePeriodActive
+        );
+
+        // Nothing has paid out before the market resolves, so answers resolved NO can still become N/A
+        if market.is_one_winner {
+            market.answers_resolved = 0;
+            market.resolved_mask = 0;
+            market.yes_mask = 0;
+            market.invalid_mask = 0;
+        }
+        for info in ctx.remaining_accounts {
+            let mut answer = Account::<Answer>::try_from(info)?;
+            require_keys_eq!(answer.market, market.key(), LikeliError::AnswerMarketMismatch);
+            if market.answer_outcome(answer.index).is_some() {
+                continue;
+            }
+
+            answer.resolved = true;
+            answer.outcome = Some(MarketOutcome::Invalid);
+            answer.exit(&crate::ID)?;
+            market.record_outcome(answer.index, MarketOutcome::Invalid);
+        }
+        require!(market.answers_resolved == market.answer_count, LikeliError::MissingSiblings);
+
+        market.abandoned = true;
+        market.resolved = true;
+
+        msg!("Multi-market {} cancelled as abandoned", market.key());
+        Ok(())
+    }
+
+    // ============== UTILITY INSTRUCTIONS ==============
+
+    /// Set fees for a market
+    pub fn set_market_fees(
+        ctx: Context<SetMarketFees>,
+        fee_bps: u16,
+        creator_fee_bps: u16,
+        platform_fee_bps: u16,
+        liquidity_fee_bps: u16,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
         
-        // Match validation: same market, same answer (if multi), opposite side
-        if order.market != orderbook.market || 
-           order.answer_index != answer_index ||
-           order.is_yes == is_yes || 
-           order.is_bid == is_buy {
-            continue; 
-        }
-        if order.qty <= order.filled_qty { continue; }
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
         
-        let price_compatible = if is_buy {
-            order.price <= limit_price
-        } else {
-            order.price >= limit_price
-        };
+        let total_fees = fee_bps as u32 + creator_fee_bps as u32 + platform_fee_bps as u32 + liquidity_fee_bps as u32;
+        require!(total_fees <= 1000, LikeliError::FeesTooHigh);
         
-        if price_compatible {
-            let available = order.qty - order.filled_qty;
-            let to_fill = remaining_amount.min(available);
-            
-            order.filled_qty += to_fill;
-            filled_amount += to_fill;
-            remaining_amount -= to_fill;
-            
-            let mut writer = &mut order_data[8..];
-            order.serialize(&mut writer)?;
-        }
+        market.fee_bps = fee_bps;
+        market.creator_fee_bps = creator_fee_bps;
+        market.platform_fee_bps = platform_fee_bps;
+        market.liquidity_fee_bps = liquidity_fee_bps;
+        
+        msg!("Fees updated: {}bps total", market.fee_bps);
+        Ok(())
     }
-    
-    Ok(MatchResult {
-        filled_amount,
-        remaining_amount,
-        matched_price: limit_price,
-    })
-}
 
-fn try_match_against_orderbook<'info>(
-    orderbook: &Orderbook,
-    remaining_accounts: &[AccountInfo<'info>],
-    answer_index: Option<u8>,
-    is_yes: bool,
-    is_buy: bool,
-    cpmm_price: u64,
-    amount: u64,
-) -> Result<MatchResult> {
-    find_matching_orders(orderbook, remaining_accounts, answer_index, is_yes, is_buy, cpmm_price, amount)
-}
+    /// Set the tick size (bps) and minimum order size for a binary market's orderbook
+    pub fn set_order_limits(
+        ctx: Context<SetMarketFees>,
+        tick_size: u16,
+        min_order_qty: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
 
-// ============== ACCOUNT CONTEXTS ==============
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(tick_size > 0 && tick_size < 10000, LikeliError::InvalidTickSize);
+        require!(min_order_qty > 0, LikeliError::InvalidAmount);
 
-#[derive(Accounts)]
-#[instruction(question: String, resolution_time: i64, initial_liquidity: u64, group_id: Option<String>, answer_label: Option<String>)]
-pub struct CreateMarket<'info> {
+        market.tick_size = tick_size;
+        market.min_order_qty = min_order_qty;
+
+        msg!("Order limits updated: tick {}bps, min qty {}", tick_size, min_order_qty);
+        Ok(())
+    }
+
+    /// Hand a binary market's resolution authority to another key, such as a
+    /// multisig or another program's PDA. Only possible before any trading.
+    pub fn set_resolver(ctx: Context<SetMarketFees>, resolver: Pubkey) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(ctx.accounts.creator.key() == market.creator, LikeliError::Unauthorized);
+        require!(market.total_volume == 0, LikeliError::MarketHasVolume);
+
+        market.resolver = resolver;
+
+        msg!("Resolver for {} set to {}", market.key(), resolver);
+        Ok(())
+    }
+
+    /// Get market price info
+    pub fn get_market_price(ctx: Context<GetMarketPrice>) -> Result<()> {
+        let market = &ctx.accounts.market;
+        
+        let total_pool = market.yes_pool.checked_add(market.no_pool).unwrap();
+        let yes_prob = (market.no_pool as u128)
+            .checked_mul(10000).unwrap()
+            .checked_div(total_pool as u128).unwrap() as u64;
+
+        msg!(
+            "Market: {} | YES: {}% | NO: {}% | Volume: {}",
+            market.question,
+            yes_prob / 100,
+            100 - (yes_prob / 100),
+            market.total_volume
+        );
+
+        Ok(())
+    }
+}
+
+// ============== HELPER FUNCTIONS ==============
+
+/// Record a binary market's outcome
+fn apply_market_resolution(market: &mut Market, outcome: MarketOutcome) -> Result<()> {
+    market.resolution_bps = match outcome {
+        MarketOutcome::Yes => 10000,
+        MarketOutcome::No | MarketOutcome::Invalid => 0,
+        MarketOutcome::Probability(bps) => {
+            require!(bps <= 10000, LikeliError::InvalidProbability);
+            bps
+        }
+    };
+    market.resolved = true;
+    market.outcome = Some(outcome);
+
+    msg!("Market resolved: {} -> {:?}", market.question, outcome);
+    Ok(())
+}
+
+/// Record an answer's outcome. In one-winner markets a YES resolves the other
+/// answers (passed in `siblings`) NO, and N/A resolves them all N/A.
+fn apply_answer_resolution<'info>(
+    market: &mut MultiMarket,
+    answer: &mut Answer,
+    outcome: MarketOutcome,
+    siblings: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    // For one-winner markets: if one answer is YES, no other can be YES
+    if market.is_one_winner && outcome == MarketOutcome::Yes {
+        require!(market.winning_answer.is_none(), LikeliError::WinnerAlreadyDeclared);
+        market.winning_answer = Some(answer.index);
+    }
+    require!(!matches!(outcome, MarketOutcome::Probability(_)), LikeliError::InvalidProbability);
+    // Answers of a one-winner market share collateral, so N/A applies to all of them
+    if market.is_one_winner && outcome == MarketOutcome::Invalid {
+        require!(market.answers_resolved == 0, LikeliError::PartialInvalidResolution);
+    }
+
+    answer.resolved = true;
+    answer.outcome = Some(outcome);
+    market.record_outcome(answer.index, outcome);
+
+    // A winner settles the rest of a one-winner market as NO; N/A cancels them all
+    if market.is_one_winner && outcome != MarketOutcome::No {
+        let sibling_outcome = if outcome == MarketOutcome::Yes { MarketOutcome::No } else { MarketOutcome::Invalid };
+        for info in siblings {
+            let mut sibling = Account::<Answer>::try_from(info)?;
+            require_keys_eq!(sibling.market, answer.market, LikeliError::AnswerMarketMismatch);
+            if sibling.resolved || sibling.index == answer.index {
+                continue;
+            }
+
+            sibling.resolved = true;
+            sibling.outcome = Some(sibling_outcome);
+            sibling.exit(&crate::ID)?;
+            market.record_outcome(sibling.index, sibling_outcome);
+        }
+        require!(market.answers_resolved == market.answer_count, LikeliError::MissingSiblings);
+    }
+
+    // Check if all answers resolved
+    if market.answers_resolved == market.answer_count {
+        market.resolved = true;
+    }
+
+    msg!("Answer {} resolved: {:?}", answer.index, outcome);
+    Ok(())
+}
+
+/// Outcome a proposal finalizes to: its own once the challenge window passes
+/// undisputed, otherwise the dispute's ruling, or N/A once voting lapses without one
+fn proposal_ruling(proposal: &Proposal) -> Result<MarketOutcome> {
+    let now = Clock::get()?.unix_timestamp;
+    if proposal.disputer.is_none() {
+        require!(now >= proposal.challenge_ends, LikeliError::ChallengeWindowOpen);
+        return Ok(proposal.outcome);
+    }
+    match proposal.ruling {
+        Some(ruling) => Ok(ruling),
+        None => {
+            require!(now >= proposal.voting_ends, LikeliError::RulingPending);
+            Ok(MarketOutcome::Invalid)
+        }
+    }
+}
+
+/// Aggregate price read from a Pyth-style (legacy v2) price account
+struct PriceFeedReading {
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+}
+
+const PRICE_FEED_MAGIC: u32 = 0xa1b2c3d4;
+const PRICE_ACCOUNT_TYPE: u32 = 3;
+const PRICE_STATUS_TRADING: u32 = 1;
+
+/// Parse the aggregate price of a Pyth-style price account
+fn read_price_feed(data: &[u8]) -> Result<PriceFeedReading> {
+    require!(data.len() >= 228, LikeliError::InvalidPriceFeed);
+    let u32_at = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    let i64_at = |at: usize| i64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+
+    require!(u32_at(0) == PRICE_FEED_MAGIC, LikeliError::InvalidPriceFeed);
+    require!(u32_at(8) == PRICE_ACCOUNT_TYPE, LikeliError::InvalidPriceFeed);
+    require!(u32_at(224) == PRICE_STATUS_TRADING, LikeliError::PriceNotTrading);
+
+    Ok(PriceFeedReading {
+        expo: u32_at(20) as i32,
+        publish_time: i64_at(96),
+        price: i64_at(208),
+        conf: i64_at(216) as u64,
+    })
+}
+
+/// Compare `a × 10^a_expo` with `b × 10^b_expo`
+fn compare_prices(a: i64, a_expo: i32, b: i64, b_expo: i32) -> std::cmp::Ordering {
+    // Scale the value with the larger exponent down to the smaller one; a
+    // shift too large to represent means that value dominates unless it is 0
+    let scale = |value: i64, shift: u32| -> i128 {
+        10i128.checked_pow(shift)
+            .and_then(|m| (value as i128).checked_mul(m))
+            .unwrap_or(value.signum() as i128 * i128::MAX)
+    };
+    let min_expo = a_expo.min(b_expo) as i64;
+    scale(a, (a_expo as i64 - min_expo) as u32).cmp(&scale(b, (b_expo as i64 - min_expo) as u32))
+}
+
+/// Return the proposer's and any disputer's bonds unchanged
+#[allow(clippy::too_many_arguments)]
+fn refund_bonds<'info>(
+    token_program: &Program<'info, Token>,
+    bond_vault: &Account<'info, TokenAccount>,
+    proposer_ata: &Account<'info, TokenAccount>,
+    disputer_ata: Option<&Account<'info, TokenAccount>>,
+    vault_authority: &UncheckedAccount<'info>,
+    market_key: &Pubkey,
+    vault_bump: u8,
+    proposal: &Proposal,
+) -> Result<()> {
+    transfer_from_vault(token_program, bond_vault, proposer_ata, vault_authority, market_key, vault_bump, proposal.bond)?;
+    if proposal.disputer.is_some() {
+        let disputer_ata = disputer_ata.ok_or(LikeliError::MissingDisputerAccount)?;
+        transfer_from_vault(token_program, bond_vault, disputer_ata, vault_authority, market_key, vault_bump, proposal.bond)?;
+    }
+    Ok(())
+}
+
+/// Committees need distinct members and a majority threshold, so no two
+/// outcomes can both reach it
+fn validate_committee(members: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(!members.is_empty() && members.len() <= MAX_COMMITTEE_MEMBERS, LikeliError::InvalidCommittee);
+    require!(
+        members.iter().enumerate().all(|(i, m)| !members[..i].contains(m)),
+        LikeliError::InvalidCommittee
+    );
+    let threshold = threshold as usize;
+    require!(threshold <= members.len() && threshold * 2 > members.len(), LikeliError::InvalidCommittee);
+    Ok(())
+}
+
+/// Committee configured on a binary or multi-choice market (default = global)
+fn market_committee(market: &AccountInfo, is_binary: bool) -> Result<Pubkey> {
+    let data = market.try_borrow_data()?;
+    if is_binary {
+        Ok(Market::try_deserialize(&mut &data[..])?.committee)
+    } else {
+        Ok(MultiMarket::try_deserialize(&mut &data[..])?.committee)
+    }
+}
+
+fn calculate_fee(amount: u64, fee_bps: u16) -> u64 {
+    if fee_bps == 0 {
+        return 0;
+    }
+    (amount as u128 * fee_bps as u128 / 10000) as u64
+}
+
+/// Payout for shares resolved at a YES probability of `bps`. Rounds down so
+/// the claims on a complete set never exceed the collateral backing it.
+fn probability_payout(yes_shares: u64, no_shares: u64, bps: u16) -> u64 {
+    let yes_value = yes_shares as u128 * bps as u128;
+    let no_value = no_shares as u128 * (10000 - bps) as u128;
+    ((yes_value + no_value) / 10000) as u64
+}
+
+/// Shift a signed cost basis by `delta`, keeping `refund_basis` — the total
+/// of the positive bases that share an N/A refund — in step
+fn shift_cost_basis(cost_basis: &mut i64, delta: i64, refund_basis: &mut u64) {
+    let before = (*cost_basis).max(0) as u64;
+    *cost_basis = cost_basis.checked_add(delta).unwrap();
+    *refund_basis = refund_basis.checked_add((*cost_basis).max(0) as u64).unwrap().saturating_sub(before);
+}
+
+/// N/A refund for a position weighted `weight` out of `refund_basis`: a
+/// pro-rata share of `pool`, so refunds never exceed the collateral left even
+/// when other positions took out more than they paid in
+fn refund_share(weight: u64, pool: u64, refund_basis: u64) -> u64 {
+    if refund_basis == 0 {
+        return 0;
+    }
+    (weight.min(refund_basis) as u128 * pool as u128 / refund_basis as u128) as u64
+}
+
+/// Pool growth and LP shares for depositing `amount` collateral at the pools'
+/// current price. The deposit mints complete sets; both pools grow by the
+/// same factor, measured against the larger pool since that is what the
+/// pool's sets are worth, and the sets the pools don't absorb go to the provider.
+fn lp_deposit(yes_pool: u64, no_pool: u64, total_lp_shares: u64, amount: u64) -> (u64, u64, u64) {
+    let pool_value = yes_pool.max(no_pool) as u128;
+    let scaled = |value: u64| (value as u128 * amount as u128 / pool_value) as u64;
+    (scaled(yes_pool), scaled(no_pool), scaled(total_lp_shares))
+}
+
+/// Pool shares released by burning `lp_shares`, rounded down so the pools
+/// keep backing everything left in them
+fn lp_withdrawal(yes_pool: u64, no_pool: u64, total_lp_shares: u64, lp_shares: u64) -> (u64, u64) {
+    let share = |pool: u64| (pool as u128 * lp_shares as u128 / total_lp_shares as u128) as u64;
+    (share(yes_pool), share(no_pool))
+}
+
+/// Constant-product buy of `amount` collateral. The collateral mints that
+/// many complete sets into both pools, then the bought side is paid out until
+/// yes_pool × no_pool is back to its old value (rounded in the pool's favour).
+/// Returns the shares bought and the new (yes_pool, no_pool).
+fn cpmm_buy(yes_pool: u64, no_pool: u64, amount: u64, is_yes: bool) -> (u64, u64, u64) {
+    let (bought, other) = if is_yes { (yes_pool, no_pool) } else { (no_pool, yes_pool) };
+    let k = bought as u128 * other as u128;
+    let other_after = other as u128 + amount as u128;
+    let bought_after = k.div_ceil(other_after);
+    let shares = (bought as u128 + amount as u128 - bought_after) as u64;
+
+    let (bought_after, other_after) = (bought_after as u64, other_after as u64);
+    if is_yes {
+        (shares, bought_after, other_after)
+    } else {
+        (shares, other_after, bought_after)
+    }
+}
+
+/// Constant-product sale of `shares`. The shares join their pool, and the
+/// pool merges `c` complete sets back into collateral for the seller, the
+/// largest `c` with (sold + shares − c)(other − c) >= sold × other.
+/// Returns the collateral paid and the new (yes_pool, no_pool).
+fn cpmm_sell(yes_pool: u64, no_pool: u64, shares: u64, is_yes: bool) -> (u64, u64, u64) {
+    let (sold, other) = if is_yes { (yes_pool, no_pool) } else { (no_pool, yes_pool) };
+    let (sold, other, s) = (sold as u128, other as u128, shares as u128);
+
+    // Smaller root of c² − (sold + s + other)·c + s·other = 0; rounding the
+    // square root up rounds c down
+    let sum = sold + s + other;
+    let discriminant = sum.checked_mul(sum).unwrap() - 4 * s * other;
+    let payout = (sum - ceil_sqrt(discriminant)) / 2;
+
+    let (sold_after, other_after) = ((sold + s - payout) as u64, (other - payout) as u64);
+    if is_yes {
+        (payout as u64, sold_after, other_after)
+    } else {
+        (payout as u64, other_after, sold_after)
+    }
+}
+
+/// Smallest integer r with r² >= x
+fn ceil_sqrt(x: u128) -> u128 {
+    if x == 0 {
+        return 0;
+    }
+    // Newton's method from above converges to floor(√x)
+    let mut r = 1u128 << (128 - x.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (r + x / r) / 2;
+        if next >= r {
+            break;
+        }
+        r = next;
+    }
+    if r * r < x { r + 1 } else { r }
+}
+
+/// Price (bps) the market maker quotes for one side of an answer
+fn multi_amm_price(market: &MultiMarket, answer: &Answer, is_yes: bool) -> Result<u64> {
+    if market.pricing == PricingEngine::Lmsr {
+        let (weights, sum) = lmsr_weights(market)?;
+        let yes_price = (weights[answer.index as usize] * 10000 / sum) as u64;
+        return Ok(if is_yes { yes_price } else { 10000 - yes_price });
+    }
+    let total_pool = answer.yes_pool.checked_add(answer.no_pool).unwrap();
+    Ok(if is_yes {
+        (answer.no_pool as u128 * 10000 / total_pool as u128) as u64
+    } else {
+        (answer.yes_pool as u128 * 10000 / total_pool as u128) as u64
+    })
+}
+
+/// e^x for a WAD fixed-point x <= 40. Floored at 1 so that LMSR weights of
+/// long-shot answers stay strictly positive.
+fn exp_wad(x: i128) -> Result<i128> {
+    require!(x <= 40 * WAD, LikeliError::LmsrOutOfRange);
+    if x < -42 * WAD {
+        return Ok(1);
+    }
+
+    // x = k·ln2 + r with |r| <= ln2/2, so e^x = 2^k · e^r
+    let k = (x + x.signum() * LN_2_WAD / 2) / LN_2_WAD;
+    let r = x - k * LN_2_WAD;
+    let mut term = WAD;
+    let mut sum = WAD;
+    let mut n = 1;
+    while term != 0 {
+        term = term * r / WAD / n;
+        sum += term;
+        n += 1;
+    }
+
+    let value = if k >= 0 { sum << k } else { sum >> -k };
+    Ok(value.max(1))
+}
+
+/// Natural log of a WAD fixed-point x > 0
+fn ln_wad(x: i128) -> Result<i128> {
+    require!(x > 0, LikeliError::LmsrOutOfRange);
+
+    // x = m · 2^k with m in [1, 2)
+    let mut m = x;
+    let mut k: i128 = 0;
+    while m >= 2 * WAD {
+        m /= 2;
+        k += 1;
+    }
+    while m < WAD {
+        m *= 2;
+        k -= 1;
+    }
+
+    // ln m = 2·atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - WAD) * WAD / (m + WAD);
+    let z2 = z * z / WAD;
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z2 / WAD;
+        n += 2;
+    }
+
+    Ok(2 * sum + k * LN_2_WAD)
+}
+
+/// LMSR weights e^((q_i - q_max) / b) in WAD for each answer, and their sum.
+/// Shifting by q_max keeps every exponent <= 0 without changing prices.
+fn lmsr_weights(market: &MultiMarket) -> Result<([i128; 10], i128)> {
+    let n = market.answer_count as usize;
+    let b = market.lmsr_b as i128;
+    let q_max = market.lmsr_q[..n].iter().copied().max().unwrap_or(0) as i128;
+
+    let mut weights = [0i128; 10];
+    for (weight, q) in weights.iter_mut().zip(&market.lmsr_q[..n]) {
+        *weight = exp_wad((*q as i128 - q_max) * WAD / b)?;
+    }
+    let sum = weights[..n].iter().sum();
+    Ok((weights, sum))
+}
+
+/// Shares an LMSR buy of `amount` collateral returns, rounded down. With
+/// S = Σ e_j and E = e^(a/b):
+/// YES: b·ln(1 + S(E − 1)/e_i), NO: b·ln((E·S − e_i)/(S − e_i))
+fn lmsr_shares_out(market: &MultiMarket, index: u8, amount: u64, is_yes: bool) -> Result<u64> {
+    let (weights, sum) = lmsr_weights(market)?;
+    let b = market.lmsr_b as i128;
+    let e_i = weights[index as usize];
+    let growth = exp_wad(amount as i128 * WAD / b)?;
+
+    let ratio = if is_yes {
+        WAD + sum * (growth - WAD) / e_i
+    } else {
+        (growth * sum / WAD - e_i) * WAD / (sum - e_i)
+    };
+    Ok((b * ln_wad(ratio)? / WAD) as u64)
+}
+
+/// Collateral an LMSR sale of `shares` returns before fees, rounded against
+/// the seller. YES: b·ln(S/(S − e_i + e_i·e^(−s/b))),
+/// NO: s − b·ln((S − e_i + e_i·e^(s/b))/S)
+fn lmsr_payout(market: &MultiMarket, index: u8, shares: u64, is_yes: bool) -> Result<u64> {
+    let (weights, sum) = lmsr_weights(market)?;
+    let b = market.lmsr_b as i128;
+    let e_i = weights[index as usize];
+    let x = shares as i128 * WAD / b;
+
+    if is_yes {
+        let shrink = exp_wad(-x)?;
+        let ratio = sum * WAD / (sum - e_i + e_i * shrink / WAD);
+        Ok((b * ln_wad(ratio)? / WAD) as u64)
+    } else {
+        let growth = exp_wad(x)?;
+        let ratio = (sum - e_i + e_i * growth / WAD) * WAD / sum;
+        Ok((shares as i128 - b * ln_wad(ratio)? / WAD - 1).max(0) as u64)
+    }
+}
+
+/// Move the LMSR share vector for a trade. NO on an answer is YES on every
+/// other answer, which is what keeps prices summing to one.
+fn lmsr_apply(market: &mut MultiMarket, index: u8, shares: u64, is_yes: bool, is_buy: bool) {
+    let delta = if is_buy { shares as i64 } else { -(shares as i64) };
+    let n = market.answer_count as usize;
+    for (i, q) in market.lmsr_q[..n].iter_mut().enumerate() {
+        if (i == index as usize) == is_yes {
+            *q = q.checked_add(delta).unwrap();
+        }
+    }
+}
+
+fn sync_sibling_pools<'info>(
+    current_answer_key: Pubkey,
+    new_price: u64, // bps
+    market_key: Pubkey,
+    expected_sibling_count: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    // Security: Validate that enough sibling accounts are passed
+    require!(
+        remaining_accounts.len() >= expected_sibling_count as usize,
+        LikeliError::MissingSiblings
+    );
+
+    msg!("Syncing siblings for answer {}. New price: {}bps. Siblings passed: {}", current_answer_key, new_price, remaining_accounts.len());
+    let mut other_answers = Vec::new();
+    let mut others_old_prob_sum: u128 = 0;
+
+    for info in remaining_accounts {
+        if info.key() == current_answer_key {
+            continue;
+        }
+        if info.owner != &crate::ID {
+            continue;
+        }
+
+        let mut data: &[u8] = &info.try_borrow_data()?;
+        if let Ok(sibling) = Answer::try_deserialize(&mut data) {
+            if sibling.market == market_key {
+                let total = sibling.yes_pool.checked_add(sibling.no_pool).unwrap();
+                if total > 0 {
+                    let p = sibling.no_pool as u128 * 10000 / total as u128;
+                    others_old_prob_sum += p;
+                    other_answers.push((info, sibling, total, p));
+                }
+            }
+        }
+    }
+
+    if other_answers.is_empty() {
+        return Ok(());
+    }
+
+    let target_others_prob_sum = 10000u32.saturating_sub(new_price as u32) as u128;
+
+    // Track actual sum for rounding error compensation
+    let mut actual_prob_sum: u128 = 0;
+    let mut last_sibling_info: Option<AccountInfo> = None;
+    let mut last_sibling: Option<Answer> = None;
+    let mut last_total: u64 = 0;
+
+    for (info, mut sibling, total, old_p) in other_answers {
+        let new_p = if others_old_prob_sum > 0 {
+            old_p.checked_mul(target_others_prob_sum).unwrap()
+                .checked_div(others_old_prob_sum).unwrap()
+        } else {
+            target_others_prob_sum.checked_div(1).unwrap()
+        };
+
+        sibling.no_pool = (total as u128 * new_p / 10000) as u64;
+        sibling.yes_pool = total.checked_sub(sibling.no_pool).unwrap();
+        
+        // Track probability for rounding compensation
+        let sibling_total = sibling.yes_pool.checked_add(sibling.no_pool).unwrap() as u128;
+        if let Some(p) = (sibling.no_pool as u128 * 10000).checked_div(sibling_total) {
+            actual_prob_sum += p;
+        }
+        
+        // Store last sibling for rounding adjustment
+        last_sibling_info = Some(info.clone());
+        last_sibling = Some(sibling.clone());
+        last_total = total;
+        
+        let mut data = info.try_borrow_mut_data()?;
+        sibling.try_serialize(&mut &mut data[..])?;
+    }
+
+    // Fix 1: Rounding error compensation - adjust last sibling to ensure sum = 100%
+    if let (Some(info), Some(mut sibling)) = (last_sibling_info, last_sibling) {
+        let rounding_error = target_others_prob_sum as i128 - actual_prob_sum as i128;
+        if rounding_error.abs() > 0 && rounding_error.abs() < 100 {
+            // Adjust no_pool by the rounding error
+            let adjustment = (last_total as i128 * rounding_error / 10000) as i64;
+            sibling.no_pool = (sibling.no_pool as i64 + adjustment).max(0) as u64;
+            sibling.yes_pool = last_total.saturating_sub(sibling.no_pool);
+            
+            let mut data = info.try_borrow_mut_data()?;
+            sibling.try_serialize(&mut &mut data[..])?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Collateral value of `qty` shares at `price` bps, rounded down
+fn order_notional(price: u64, qty: u64) -> u64 {
+    (price as u128 * qty as u128 / 10000) as u64
+}
+
+/// Collateral exchanged when `fill` shares of a resting order with `remaining`
+/// shares trade at its price. Computed cumulatively so partial fills always
+/// sum to the notional escrowed when the order was rested.
+fn fill_notional(price: u64, remaining: u64, fill: u64) -> u64 {
+    order_notional(price, remaining) - order_notional(price, remaining - fill)
+}
+
+/// Largest fill of a resting order at `maker_price` with `remaining` shares
+/// whose taker cost fits in `budget`. Inverts `fill_notional` in closed form:
+/// the taker pays the maker's notional for direct fills and the rest of each
+/// complete set for minted ones, both non-decreasing in the fill.
+fn max_affordable_fill(maker_price: u64, remaining: u64, budget: u64, is_complement: bool) -> u64 {
+    let (price, remaining, budget) = (maker_price as u128, remaining as u128, budget as u128);
+
+    // Shares that must stay unfilled: the fewest leaving the cost within budget
+    let unfilled = if is_complement {
+        // cost = ceil(remaining·q) − ceil(unfilled·q) with q = (10000 − price) / 10000
+        let complement = 10000 - price;
+        let total = (remaining * complement).div_ceil(10000);
+        if total <= budget { 0 } else { (total - budget - 1) * 10000 / complement + 1 }
+    } else {
+        // cost = floor(remaining·p) − floor(unfilled·p) with p = price / 10000
+        let total = remaining * price / 10000;
+        if total <= budget { 0 } else { ((total - budget) * 10000).div_ceil(price) }
+    };
+    (remaining - unfilled.min(remaining)) as u64
+}
+
+/// Apply an owner's accrued orderbook balance to their position.
+/// Returns the collateral owed to them; their slot is freed once no orders rest.
+fn settle_owner_balance(
+    orderbook: &mut Orderbook,
+    owner: &Pubkey,
+    user_position: Option<&mut UserPosition>,
+    multi_position: Option<&mut MultiPosition>,
+    refund_basis: &mut u64,
+) -> Result<u64> {
+    let slot = orderbook.find_owner(owner).ok_or(LikeliError::NothingToSettle)?;
+    let answer_index = (orderbook.is_multi != 0).then_some(orderbook.answer_index);
+    let balance = &mut orderbook.balances[slot];
+    require!(balance.has_unsettled(), LikeliError::NothingToSettle);
+
+    match answer_index {
+        None => {
+            let position = user_position.ok_or(LikeliError::MissingPosition)?;
+            position.settle_fill(true, true, balance.yes_bought);
+            position.settle_fill(false, true, balance.no_bought);
+            position.settle_fill(true, false, balance.yes_sold);
+            position.settle_fill(false, false, balance.no_sold);
+            position.unlock_shares(true, balance.yes_unlocked);
+            position.unlock_shares(false, balance.no_unlocked);
+            position.add_cost_basis(balance.bid_cost, refund_basis);
+            position.reduce_cost_basis(balance.ask_proceeds, refund_basis);
+        }
+        Some(index) => {
+            let position = multi_position.ok_or(LikeliError::MissingPosition)?;
+            position.settle_fill(index, true, true, balance.yes_bought);
+            position.settle_fill(index, false, true, balance.no_bought);
+            position.settle_fill(index, true, false, balance.yes_sold);
+            position.settle_fill(index, false, false, balance.no_sold);
+            position.unlock_shares(index, true, balance.yes_unlocked);
+            position.unlock_shares(index, false, balance.no_unlocked);
+            position.add_cost_basis(index, balance.bid_cost, refund_basis);
+            position.reduce_cost_basis(index, balance.ask_proceeds, refund_basis);
+        }
+    }
+
+    let collateral = balance.collateral;
+    balance.clear_settled();
+    if balance.open_orders == 0 {
+        balance.owner = Pubkey::default();
+    }
+
+    Ok(collateral)
+}
+
+/// Run `f` on the N/A refund total an orderbook's settled fills count
+/// toward: the binary market's, or that of the book's answer
+fn with_refund_basis<R>(
+    market: &AccountInfo,
+    answer_index: Option<u8>,
+    f: impl FnOnce(&mut u64) -> Result<R>,
+) -> Result<R> {
+    let mut data = market.try_borrow_mut_data()?;
+    match answer_index {
+        None => {
+            let mut market = Market::try_deserialize(&mut &data[..])?;
+            let result = f(&mut market.refund_basis)?;
+            market.try_serialize(&mut &mut data[..])?;
+            Ok(result)
+        }
+        Some(index) => {
+            let mut market = MultiMarket::try_deserialize(&mut &data[..])?;
+            let result = f(&mut market.refund_basis[index as usize])?;
+            market.try_serialize(&mut &mut data[..])?;
+            Ok(result)
+        }
+    }
+}
+
+/// Fail unless the owner has no resting orders or unsettled fills in the book
+fn require_book_idle(orderbook: &Orderbook, owner: &Pubkey) -> Result<()> {
+    if let Some(slot) = orderbook.find_owner(owner) {
+        require!(orderbook.balances[slot].is_idle(), LikeliError::UnsettledOrders);
+    }
+    Ok(())
+}
+
+/// Settle an owner's orderbook balance on their behalf, for instructions
+/// acting on someone else's orders. Credits their position account and
+/// returns their token account with the collateral owed to it. An empty
+/// balance just frees the owner's slot.
+fn settle_for_owner<'info>(
+    orderbook: &mut Orderbook,
+    owner: &Pubkey,
+    position: &'info AccountInfo<'info>,
+    owner_ata: &'info AccountInfo<'info>,
+    mint: &Pubkey,
+    refund_basis: &mut u64,
+) -> Result<(Account<'info, TokenAccount>, u64)> {
+    let owner_ata = Account::<TokenAccount>::try_from(owner_ata)?;
+    require!(owner_ata.owner == *owner && owner_ata.mint == *mint, LikeliError::OwnerAccountMismatch);
+
+    let slot = orderbook.find_owner(owner).ok_or(LikeliError::NothingToSettle)?;
+    if !orderbook.balances[slot].has_unsettled() {
+        if orderbook.balances[slot].open_orders == 0 {
+            orderbook.balances[slot].owner = Pubkey::default();
+        }
+        return Ok((owner_ata, 0));
+    }
+
+    let market = orderbook.market;
+    let payout = if orderbook.is_multi != 0 {
+        let mut position = Account::<MultiPosition>::try_from(position)?;
+        require!(position.owner == *owner && position.market == market, LikeliError::OwnerAccountMismatch);
+        let payout = settle_owner_balance(orderbook, owner, None, Some(&mut position), refund_basis)?;
+        position.exit(&crate::ID)?;
+        payout
+    } else {
+        let mut position = Account::<UserPosition>::try_from(position)?;
+        require!(position.owner == *owner && position.market == market, LikeliError::OwnerAccountMismatch);
+        let payout = settle_owner_balance(orderbook, owner, Some(&mut position), None, refund_basis)?;
+        position.exit(&crate::ID)?;
+        payout
+    };
+    Ok((owner_ata, payout))
+}
+
+/// Transfer collateral out of a token account owned by the market's vault authority
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    vault_authority: &UncheckedAccount<'info>,
+    market_key: &Pubkey,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let seeds = &[
+        VAULT_SEED,
+        market_key.as_ref(),
+        &[vault_bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault_authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            cpi_accounts,
+            signer,
+        ),
+        amount,
+    )
+}
+
+/// Move makers' collateral for minted or merged complete sets: bid escrow
+/// leaves the order vault to back minted sets, and ask proceeds from merged
+/// sets wait in the order vault for settlement
+#[allow(clippy::too_many_arguments)]
+fn transfer_maker_set_collateral<'info>(
+    token_program: &Program<'info, Token>,
+    order_vault: &Account<'info, TokenAccount>,
+    vault_ata: &Account<'info, TokenAccount>,
+    vault_authority: &UncheckedAccount<'info>,
+    market_key: &Pubkey,
+    vault_bump: u8,
+    match_result: &MatchResult,
+    taker_is_buy: bool,
+) -> Result<()> {
+    let (from, to) = if taker_is_buy { (order_vault, vault_ata) } else { (vault_ata, order_vault) };
+    transfer_from_vault(
+        token_program,
+        from,
+        to,
+        vault_authority,
+        market_key,
+        vault_bump,
+        match_result.maker_set_notional,
+    )
+}
+
+/// Transfer collateral from a user's token account into a program-owned token account
+fn transfer_to_vault<'info>(
+    token_program: &Program<'info, Token>,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    authority: &Signer<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            cpi_accounts,
+        ),
+        amount,
+    )
+}
+
+/// How an incoming order treats the part that doesn't fill immediately
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderType {
+    /// Good till cancelled: the remainder rests in the book
+    Limit,
+    /// Immediate or cancel: fill what crosses, drop the remainder
+    ImmediateOrCancel,
+    /// Fill the whole quantity immediately or fail
+    FillOrKill,
+    /// Rest the whole order without taking; fail if it would cross
+    PostOnly,
+    /// Take liquidity up to the order price as the worst acceptable price;
+    /// fail if nothing fills, drop the remainder
+    Market,
+}
+
+impl OrderType {
+    /// Whether the unfilled remainder rests in the book
+    pub fn rests(&self) -> bool {
+        matches!(self, OrderType::Limit | OrderType::PostOnly)
+    }
+}
+
+/// What happens when an order would match a resting order from the same owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelfTradeMode {
+    /// Stop matching and drop the incoming order's remainder
+    CancelTaker,
+    /// Cancel the resting order and keep matching
+    CancelMaker,
+    /// Reduce both orders by the overlapping size without trading
+    DecrementBoth,
+}
+
+/// Taker side of a match attempt
+#[derive(Clone, Copy, Debug)]
+pub struct TakerOrder {
+    pub owner: Pubkey,
+    pub is_yes: bool,
+    pub is_buy: bool,
+    pub limit_price: u64,
+    /// Shares to fill, or collateral to spend if `amount_is_collateral`
+    pub amount: u64,
+    pub amount_is_collateral: bool,
+    pub self_trade: SelfTradeMode,
+}
+
+impl TakerOrder {
+    /// Whether a resting order at `price` is acceptable to this taker
+    pub fn crosses(&self, price: u64) -> bool {
+        if self.is_buy { price <= self.limit_price } else { price >= self.limit_price }
+    }
+}
+
+/// Result of order matching attempt
+#[derive(Clone, Copy, Debug)]
+pub struct MatchResult {
+    /// Shares filled against resting orders
+    pub filled_amount: u64,
+    /// Unfilled part of the taker amount, in the taker's units
+    pub remaining_amount: u64,
+    /// Collateral the taker paid or received in total
+    pub notional: u64,
+    /// Part of `notional` paid into or out of the market vault for minted or merged complete sets
+    pub set_notional: u64,
+    /// Makers' collateral moving between the order vault and the market vault for those sets
+    pub maker_set_notional: u64,
+    /// Taker amount dropped by self-trade prevention, in the taker's units
+    pub cancelled_amount: u64,
+}
+
+impl MatchResult {
+    /// Collateral the taker exchanged with resting orders on the same outcome
+    pub fn direct_notional(&self) -> u64 {
+        self.notional - self.set_notional
+    }
+}
+
+/// Match a taker against the book and fill resting orders at each maker's price
+///
+/// Matching logic:
+/// - Same outcome, opposite side: shares trade directly. Buy orders match
+///   sell orders at price <= buy_price, sell orders match buy orders at
+///   price >= sell_price
+/// - Other outcome, same side: complete sets are minted or merged, with the
+///   same vault accounting as `split_position`/`merge_positions`. A YES bid at
+///   p matches a NO bid at >= 10000 - p and both pay their own price into the
+///   market vault; a YES ask at p matches a NO ask at <= 10000 - p and the
+///   merged collateral is split between them
+/// - Each fill takes whichever source gives the taker the better price; ties
+///   go to the older order, so price-time priority holds across both
+/// - Expired orders are evicted instead of filled, and maker remainders below
+///   `min_order_qty` leave the book instead of resting as dust
+/// - Orders from the taker's own owner never trade; see `SelfTradeMode`
+///
+/// Makers are credited in their orderbook balance and collect through
+/// `settle_funds`/`cancel_order`; the caller settles the taker side using
+/// the returned notionals.
+fn match_orders(
+    orderbook: &mut Orderbook,
+    taker: &TakerOrder,
+    now: i64,
+    min_order_qty: u64,
+) -> Result<MatchResult> {
+    let mut filled_amount: u64 = 0;
+    let mut notional: u64 = 0;
+    let mut set_notional: u64 = 0;
+    let mut maker_set_notional: u64 = 0;
+    let mut remaining_amount = taker.amount;
+    let mut cancelled_amount: u64 = 0;
+
+    while remaining_amount > 0 {
+        // Sides are kept in priority order, so nothing behind the best order crosses either
+        let Some((is_complement, price)) = orderbook.best_source(taker, now) else { break };
+        if !taker.crosses(price) { break; }
+
+        let maker_is_yes = taker.is_yes != is_complement;
+        let maker_is_bid = taker.is_buy == is_complement;
+        let side = &mut orderbook.sides[side_index(maker_is_yes, maker_is_bid)];
+        let Some(best) = side.best_mut() else { break };
+
+        let self_trade = best.owner == taker.owner;
+        if self_trade {
+            match taker.self_trade {
+                SelfTradeMode::CancelTaker => {
+                    cancelled_amount += remaining_amount;
+                    remaining_amount = 0;
+                    break;
+                }
+                SelfTradeMode::CancelMaker => {
+                    let own = *best;
+                    side.len -= 1;
+                    orderbook.balances[own.owner_slot as usize].release(&own, maker_is_yes, maker_is_bid);
+                    continue;
+                }
+                SelfTradeMode::DecrementBoth => {}
+            }
+        }
+
+        // Collateral the taker pays or receives: the maker's price for direct
+        // fills, the rest of each complete set for minted or merged fills
+        let maker_price = best.price as u64;
+        let resting_qty = best.qty;
+        let taker_cost = |fill: u64| {
+            let maker_cost = fill_notional(maker_price, resting_qty, fill);
+            if is_complement { fill - maker_cost } else { maker_cost }
+        };
+
+        let to_fill = if taker.amount_is_collateral {
+            max_affordable_fill(maker_price, resting_qty, remaining_amount, is_complement)
+        } else {
+            remaining_amount.min(resting_qty)
+        };
+        let cost = taker_cost(to_fill);
+        // Budget can't buy a single share at the best price
+        if to_fill == 0 { break; }
+        let maker_cost = fill_notional(maker_price, resting_qty, to_fill);
+
+        best.qty -= to_fill;
+        let filled_value = order_notional(maker_price, resting_qty) - order_notional(maker_price, best.qty);
+        let taker_units = if taker.amount_is_collateral { cost } else { to_fill };
+        remaining_amount -= taker_units;
+
+        let balance = &mut orderbook.balances[best.owner_slot as usize];
+        balance.resting -= filled_value;
+        if self_trade {
+            // Nothing trades: release the maker's escrow for the decremented shares
+            cancelled_amount += taker_units;
+            balance.release_escrow(maker_is_yes, maker_is_bid, to_fill, maker_cost);
+        } else {
+            filled_amount += to_fill;
+            notional += cost;
+            if is_complement {
+                set_notional += cost;
+                maker_set_notional += maker_cost;
+            }
+            balance.credit_fill(maker_is_yes, maker_is_bid, to_fill, maker_cost);
+        }
+
+        // Exhausted orders leave the book, as do remainders below the minimum size
+        if best.qty < min_order_qty.max(1) {
+            let exhausted = *best;
+            side.len -= 1;
+            balance.release(&exhausted, maker_is_yes, maker_is_bid);
+        }
+    }
+    
+    Ok(MatchResult {
+        filled_amount,
+        remaining_amount,
+        notional,
+        set_notional,
+        maker_set_notional,
+        cancelled_amount,
+    })
+}
+
+/// Match an order placed on the book according to its order type
+fn match_incoming_order(
+    orderbook: &mut Orderbook,
+    taker: &TakerOrder,
+    order_type: OrderType,
+    now: i64,
+    min_order_qty: u64,
+) -> Result<MatchResult> {
+    if order_type == OrderType::PostOnly {
+        let best = orderbook.best_source(taker, now);
+        require!(!best.is_some_and(|(_, price)| taker.crosses(price)), LikeliError::PostOnlyWouldCross);
+        return Ok(MatchResult {
+            filled_amount: 0,
+            remaining_amount: taker.amount,
+            notional: 0,
+            set_notional: 0,
+            maker_set_notional: 0,
+            cancelled_amount: 0,
+        });
+    }
+
+    let match_result = match_orders(orderbook, taker, now, min_order_qty)?;
+    match order_type {
+        OrderType::FillOrKill => require!(match_result.filled_amount == taker.amount, LikeliError::OrderNotFilled),
+        OrderType::Market => require!(match_result.filled_amount > 0, LikeliError::NoLiquidity),
+        _ => {}
+    }
+    Ok(match_result)
+}
+
+/// Match a CPMM trade against the book before it reaches the pool.
+/// Buys spend `amount` collateral; sells fill `amount` shares.
+/// Markets without an orderbook trade against the pool only. The trader's own
+/// crossing orders are cancelled so pool trades can't wash against them.
+#[allow(clippy::too_many_arguments)]
+fn try_match_against_orderbook(
+    orderbook: Option<&AccountLoader<Orderbook>>,
+    market_orderbook: Pubkey,
+    is_yes: bool,
+    is_buy: bool,
+    cpmm_price: u64,
+    amount: u64,
+    trader: Pubkey,
+    min_order_qty: u64,
+) -> Result<MatchResult> {
+    let Some(orderbook) = orderbook else {
+        require_keys_eq!(market_orderbook, Pubkey::default(), LikeliError::MissingOrderbook);
+        return Ok(MatchResult {
+            filled_amount: 0,
+            remaining_amount: amount,
+            notional: 0,
+            set_notional: 0,
+            maker_set_notional: 0,
+            cancelled_amount: 0,
+        });
+    };
+
+    match_orders(
+        &mut *orderbook.load_mut()?,
+        &TakerOrder {
+            owner: trader,
+            is_yes,
+            is_buy,
+            limit_price: cpmm_price,
+            amount,
+            amount_is_collateral: is_buy,
+            self_trade: SelfTradeMode::CancelMaker,
+        },
+        Clock::get()?.unix_timestamp,
+        min_order_qty,
+    )
+}
+
+/// Check an order against its market's tick size and minimum order size
+fn check_order_rules(price: u64, qty: u64, tick_size: u16, min_order_qty: u64) -> Result<()> {
+    require!(price.is_multiple_of(tick_size as u64), LikeliError::InvalidTickSize);
+    require!(qty >= min_order_qty, LikeliError::OrderTooSmall);
+    Ok(())
+}
+
+/// Order placement rules of the market an orderbook belongs to
+struct OrderRules {
+    tick_size: u16,
+    min_order_qty: u64,
+    close_time: i64,
+}
+
+fn market_order_rules(market: &AccountInfo, is_multi: bool) -> Result<OrderRules> {
+    let data = market.try_borrow_data()?;
+    if is_multi {
+        let market = MultiMarket::try_deserialize(&mut &data[..])?;
+        Ok(OrderRules { tick_size: market.tick_size, min_order_qty: market.min_order_qty, close_time: market.close_time })
+    } else {
+        let market = Market::try_deserialize(&mut &data[..])?;
+        Ok(OrderRules { tick_size: market.tick_size, min_order_qty: market.min_order_qty, close_time: market.close_time })
+    }
+}
+
+// ============== ACCOUNT CONTEXTS ==============
+
+#[derive(Accounts)]
+#[instruction(question: String, resolution_time: i64, initial_liquidity: u64, group_id: Option<String>, answer_label: Option<String>)]
+pub struct CreateMarket<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [
+            b"market".as_ref(), 
+            creator.key().as_ref(), 
+            &question.as_bytes()[..15.min(question.len())],
+            match &answer_label {
+                Some(a) => &a.as_bytes()[..15.min(a.len())],
+                None => &b"binary"[..]
+            }
+        ],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateOrderbook<'info> {
+    /// Orderbook account allocated by the client with `Orderbook::SPACE` bytes
+    #[account(zero)]
+    pub orderbook: AccountLoader<'info, Orderbook>,
+    
+    #[account(
+        mut,
+        constraint = creator.key() == market.creator @ LikeliError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMultiOrderbook<'info> {
+    /// Orderbook account allocated by the client with `Orderbook::SPACE` bytes
+    #[account(zero)]
+    pub orderbook: AccountLoader<'info, Orderbook>,
+    
+    #[account(
+        mut,
+        constraint = creator.key() == market.creator @ LikeliError::Unauthorized
+    )]
+    pub market: Account<'info, MultiMarket>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BuyShares<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    
+    /// Market orderbook, required once one has been created
+    #[account(mut, address = market.orderbook @ LikeliError::OrderbookMismatch)]
+    pub orderbook: Option<AccountLoader<'info, Orderbook>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", market.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Vault's token account holding collateral
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    /// Token account escrowing collateral for resting bids
+    #[account(
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Trader's token account (source on buy, destination on sell)
+    #[account(
+        mut,
+        constraint = buyer_ata.owner == buyer.key(),
+        constraint = buyer_ata.mint == vault_ata.mint
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BinaryLiquidity<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", market.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    /// Receives outcome shares the pools don't absorb
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", market.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = provider_ata.owner == provider.key(),
+        constraint = provider_ata.mint == vault_ata.mint
+    )]
+    pub provider_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(mut, constraint = market.resolved @ LikeliError::MarketNotResolved)]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    pub claimer: Signer<'info>,
+}
+
+/// Claim winnings with actual token transfer
+#[derive(Accounts)]
+pub struct ClaimWinningsWithVault<'info> {
+    #[account(mut, constraint = market.resolved @ LikeliError::MarketNotResolved)]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), claimer.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    /// Market's orderbook, required for N/A refunds once it exists
+    #[account(address = market.orderbook @ LikeliError::OrderbookMismatch)]
+    pub orderbook: Option<AccountLoader<'info, Orderbook>>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    
+    /// Vault's token account holding collateral
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    
+    /// Claimer's token account to receive payout
+    #[account(
+        mut,
+        constraint = claimer_ata.owner == claimer.key()
+    )]
+    pub claimer_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(question_hash: [u8; 32], answer_count: u8)]
+pub struct CreateMultiMarket<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + MultiMarket::INIT_SPACE,
+        seeds = [b"multi_market", creator.key().as_ref(), question_hash.as_ref()],
+        bump
+    )]
+    pub market: Account<'info, MultiMarket>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMultiMarketConfig<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableLmsr<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_ata.owner == creator.key(),
+        constraint = creator_ata.mint == vault_ata.mint
+    )]
+    pub creator_ata: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMarketVault<'info> {
+    #[account(
+        mut,
+        constraint = creator.key() == market.creator @ LikeliError::Unauthorized
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Vault's token account for holding collateral
+    #[account(
+        init,
+        payer = creator,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    /// Token account escrowing collateral for resting bids
+    #[account(
+        init,
+        payer = creator,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Collateral token mint (e.g., USDC)
+    pub collateral_mint: Account<'info, Mint>,
+
+    /// Creator's token account funding the initial liquidity
+    #[account(
+        mut,
+        constraint = creator_ata.owner == creator.key(),
+        constraint = creator_ata.mint == collateral_mint.key()
+    )]
+    pub creator_ata: Account<'info, TokenAccount>,
+
+    /// Creator's LP position for the seed liquidity
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", market.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    /// Creator's position, recording the seed in its cost basis
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", market.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    pub market: Account<'info, MultiMarket>,
+    
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    
+    /// Vault's token account for holding collateral
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    /// Token account escrowing collateral for resting bids
+    #[account(
+        init,
+        payer = payer,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+    
+    /// Collateral token mint (e.g., USDC)
+    pub collateral_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct AddAnswer<'info> {
+    #[account(
+        mut,
+        constraint = creator.key() == market.creator @ LikeliError::Unauthorized
+    )]
+    pub market: Account<'info, MultiMarket>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Answer::INIT_SPACE,
+        seeds = [b"answer", market.key().as_ref(), &[index]],
+        bump
+    )]
+    pub answer: Account<'info, Answer>,
+
+    /// Creator's LP position for the answer's seed liquidity
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", answer.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    /// Receives the seed's NO shares the pool doesn't hold
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MultiPosition::INIT_SPACE,
+        seeds = [b"multi_position", market.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = creator_ata.owner == creator.key(),
+        constraint = creator_ata.mint == vault_ata.mint
+    )]
+    pub creator_ata: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AnswerLiquidity<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+
+    #[account(mut, constraint = answer.market == market.key())]
+    pub answer: Account<'info, Answer>,
+
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + LpPosition::INIT_SPACE,
+        seeds = [b"lp_position", answer.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub lp_position: Account<'info, LpPosition>,
+
+    /// Receives outcome shares the pools don't absorb
+    #[account(
+        init_if_needed,
+        payer = provider,
+        space = 8 + MultiPosition::INIT_SPACE,
+        seeds = [b"multi_position", market.key().as_ref(), provider.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = provider_ata.owner == provider.key(),
+        constraint = provider_ata.mint == vault_ata.mint
+    )]
+    pub provider_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub provider: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BuyMulti<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+    
+    #[account(mut, constraint = answer.market == market.key())]
+    pub answer: Account<'info, Answer>,
+
+    /// Answer orderbook, required once one has been created
+    #[account(mut, address = market.orderbooks[answer.index as usize] @ LikeliError::OrderbookMismatch)]
+    pub orderbook: Option<AccountLoader<'info, Orderbook>>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + MultiPosition::INIT_SPACE,
+        seeds = [b"multi_position", market.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    /// Vault authority PDA
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Vault's token account holding collateral
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    /// Token account escrowing collateral for resting bids
+    #[account(
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Trader's token account (source on buy, destination on sell)
+    #[account(
+        mut,
+        constraint = buyer_ata.owner == buyer.key(),
+        constraint = buyer_ata.mint == vault_ata.mint
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConvertPositionsWithVault<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+    
+    #[account(
+        mut,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    /// Vault authority PDA (signs for vault transfers)
+    /// CHECK: Vault authority is a PDA
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+    
+    /// Vault's token account holding collateral
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    
+    /// User's token account to receive collateral
     #[account(
-        init,
-        payer = creator,
-        space = 8 + Market::INIT_SPACE,
-        seeds = [
-            b"market".as_ref(), 
-            creator.key().as_ref(), 
-            &question.as_bytes()[..15.min(question.len())],
-            match &answer_label {
-                Some(a) => &a.as_bytes()[..15.min(a.len())],
-                None => &b"binary"[..]
-            }
-        ],
+        mut,
+        constraint = user_ata.owner == owner.key()
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+    
+    /// Fee vault's token account
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED],
         bump
     )]
-    pub market: Account<'info, Market>,
+    pub fee_vault_ata: Account<'info, TokenAccount>,
     
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub owner: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct CreateOrderbook<'info> {
+pub struct SplitPositionWithVault<'info> {
+    #[account(mut)]
+    pub market: Account<'info, MultiMarket>,
+    
+    pub answer: Account<'info, Answer>,
+    
     #[account(
-        init,
-        payer = creator,
-        space = 8 + Orderbook::INIT_SPACE,
-        seeds = [b"orderbook", market.key().as_ref()],
+        mut,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    /// Vault authority PDA (signs for vault transfers)
+    /// CHECK: Vault authority is a PDA
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
-    pub orderbook: Account<'info, Orderbook>,
+    pub vault_authority: UncheckedAccount<'info>,
     
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+    /// Vault's token account holding collateral
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+    
+    /// User's token account
+    #[account(
+        mut,
+        constraint = user_ata.owner == owner.key()
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
     
     #[account(mut)]
-    pub creator: Signer<'info>,
+    pub owner: Signer<'info>,
     
-    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
+// Keep old contexts for backwards compatibility
 #[derive(Accounts)]
-pub struct BuyShares<'info> {
-    #[account(mut)]
-    pub market: Account<'info, Market>,
+pub struct ConvertPositions<'info> {
+    pub market: Account<'info, MultiMarket>,
     
     #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8 + Orderbook::INIT_SPACE,
-        seeds = [b"orderbook", market.key().as_ref()],
+        mut,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
         bump
     )]
-    pub orderbook: Account<'info, Orderbook>,
+    pub position: Account<'info, MultiPosition>,
+
+    pub owner: Signer<'info>,
+}
 
+#[derive(Accounts)]
+pub struct SplitPosition<'info> {
+    pub market: Account<'info, MultiMarket>,
+    
+    pub answer: Account<'info, Answer>,
+    
     #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8 + UserPosition::INIT_SPACE,
-        seeds = [b"position", market.key().as_ref(), buyer.key().as_ref()],
+        mut,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
         bump
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub position: Account<'info, MultiPosition>,
 
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveAnswer<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub market: Account<'info, MultiMarket>,
+    
+    #[account(mut, constraint = answer.market == market.key())]
+    pub answer: Account<'info, Answer>,
+    
+    pub resolver: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimWinnings<'info> {
-    #[account(constraint = market.resolved @ LikeliError::MarketNotResolved)]
-    pub market: Account<'info, Market>,
+pub struct ClaimMultiWinnings<'info> {
+    #[account(mut, constraint = market.resolved @ LikeliError::MarketNotResolved)]
+    pub market: Account<'info, MultiMarket>,
     
     #[account(
         mut,
-        seeds = [b"position", market.key().as_ref(), claimer.key().as_ref()],
+        seeds = [b"multi_position", market.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub position: Account<'info, MultiPosition>,
 
     pub claimer: Signer<'info>,
 }
 
-/// Claim winnings with actual token transfer
+/// Claim multi-choice winnings with actual token transfer
 #[derive(Accounts)]
-pub struct ClaimWinningsWithVault<'info> {
-    #[account(constraint = market.resolved @ LikeliError::MarketNotResolved)]
-    pub market: Account<'info, Market>,
+pub struct ClaimMultiWinningsWithVault<'info> {
+    #[account(mut, constraint = market.resolved @ LikeliError::MarketNotResolved)]
+    pub market: Account<'info, MultiMarket>,
     
     #[account(
         mut,
-        seeds = [b"position", market.key().as_ref(), claimer.key().as_ref()],
+        seeds = [b"multi_position", market.key().as_ref(), claimer.key().as_ref()],
         bump
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub position: Account<'info, MultiPosition>,
 
     /// Vault authority PDA
     /// CHECK: This is a PDA controlled by the program
@@ -1497,6 +4636,8 @@ pub struct ClaimWinningsWithVault<'info> {
     /// Vault's token account holding collateral
     #[account(
         mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
         constraint = vault_ata.owner == vault_authority.key()
     )]
     pub vault_ata: Account<'info, TokenAccount>,
@@ -1515,396 +4656,580 @@ pub struct ClaimWinningsWithVault<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(question_hash: [u8; 32], answer_count: u8)]
-pub struct CreateMultiMarket<'info> {
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    
+    #[account(mut, address = market.orderbook @ LikeliError::OrderbookMismatch)]
+    pub orderbook: AccountLoader<'info, Orderbook>,
+
     #[account(
-        init,
-        payer = creator,
-        space = 8 + MultiMarket::INIT_SPACE,
-        seeds = [b"multi_market", creator.key().as_ref(), question_hash.as_ref()],
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", market.key().as_ref(), owner.key().as_ref()],
         bump
     )]
-    pub market: Account<'info, MultiMarket>,
-    
+    pub position: Account<'info, UserPosition>,
+
+    /// Vault authority PDA (signs for order vault transfers)
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Token account escrowing collateral for resting bids
+    #[account(
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Vault's token account backing complete sets
+    #[account(
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
+    )]
+    pub vault_ata: Account<'info, TokenAccount>,
+
+    /// Owner's token account funding bid escrow and receiving ask proceeds
+    #[account(
+        mut,
+        constraint = owner_ata.owner == owner.key(),
+        constraint = owner_ata.mint == order_vault.mint
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
     #[account(mut)]
-    pub creator: Signer<'info>,
-    
+    pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct SetMultiMarketConfig<'info> {
+#[instruction(answer_index: u8)]
+pub struct PlaceMultiOrder<'info> {
     #[account(mut)]
     pub market: Account<'info, MultiMarket>,
     
-    pub creator: Signer<'info>,
-}
+    #[account(
+        mut,
+        constraint = market.orderbooks.get(answer_index as usize) == Some(&orderbook.key()) @ LikeliError::OrderbookMismatch
+    )]
+    pub orderbook: AccountLoader<'info, Orderbook>,
 
-#[derive(Accounts)]
-pub struct InitializeVault<'info> {
-    pub market: Account<'info, MultiMarket>,
-    
-    /// Vault authority PDA
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + MultiPosition::INIT_SPACE,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, MultiPosition>,
+
+    /// Vault authority PDA (signs for order vault transfers)
     /// CHECK: This is a PDA controlled by the program
     #[account(
         seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
     pub vault_authority: UncheckedAccount<'info>,
-    
-    /// Vault's token account for holding collateral
+
+    /// Token account escrowing collateral for resting bids
     #[account(
-        init,
-        payer = payer,
-        token::mint = collateral_mint,
-        token::authority = vault_authority,
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Vault's token account backing complete sets
+    #[account(
+        mut,
         seeds = [b"vault_ata", market.key().as_ref()],
-        bump
+        bump,
+        constraint = vault_ata.owner == vault_authority.key()
     )]
     pub vault_ata: Account<'info, TokenAccount>,
-    
-    /// Collateral token mint (e.g., USDC)
-    pub collateral_mint: Account<'info, Mint>,
-    
+
+    /// Owner's token account funding bid escrow and receiving ask proceeds
+    #[account(
+        mut,
+        constraint = owner_ata.owner == owner.key(),
+        constraint = owner_ata.mint == order_vault.mint
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
     #[account(mut)]
-    pub payer: Signer<'info>,
-    
+    pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-#[instruction(index: u8)]
-pub struct AddAnswer<'info> {
+pub struct CancelOrder<'info> {
+    #[account(mut)]
+    pub orderbook: AccountLoader<'info, Orderbook>,
+
+    /// Market the orderbook belongs to; settled fills update its N/A refund total
+    /// CHECK: Derives PDAs and is read through `with_refund_basis`; pinned to the orderbook's market
+    #[account(mut, address = orderbook.load()?.market @ LikeliError::OrderbookMismatch)]
+    pub market: UncheckedAccount<'info>,
+
+    /// Vault authority PDA (signs for order vault transfers)
+    /// CHECK: This is a PDA controlled by the program
     #[account(
-        constraint = creator.key() == market.creator @ LikeliError::Unauthorized
+        seeds = [VAULT_SEED, market.key().as_ref()],
+        bump
     )]
-    pub market: Account<'info, MultiMarket>,
-    
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Token account escrowing collateral for resting bids
     #[account(
-        init,
-        payer = creator,
-        space = 8 + Answer::INIT_SPACE,
-        seeds = [b"answer", market.key().as_ref(), &[index]],
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
+    )]
+    pub order_vault: Account<'info, TokenAccount>,
+
+    /// Owner's token account receiving refunded bid escrow and ask proceeds
+    #[account(
+        mut,
+        constraint = owner_ata.owner == owner.key(),
+        constraint = owner_ata.mint == order_vault.mint
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    /// Binary position receiving fills and unlocked ask shares (binary books only)
+    #[account(
+        mut,
+        seeds = [b"position", market.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_position: Option<Account<'info, UserPosition>>,
+
+    /// Multi-choice position receiving fills and unlocked ask shares (multi-choice books only)
+    #[account(
+        mut,
+        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
         bump
     )]
-    pub answer: Account<'info, Answer>,
-    
-    #[account(mut)]
-    pub creator: Signer<'info>,
+    pub multi_position: Option<Account<'info, MultiPosition>>,
     
-    pub system_program: Program<'info, System>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct BuyMulti<'info> {
+pub struct EvictOrders<'info> {
     #[account(mut)]
-    pub market: Account<'info, MultiMarket>,
-    
-    #[account(mut, constraint = answer.market == market.key())]
-    pub answer: Account<'info, Answer>,
+    pub orderbook: AccountLoader<'info, Orderbook>,
 
+    /// Market the orderbook belongs to; settled fills update its N/A refund total
+    /// CHECK: Derives PDAs and is read through `with_refund_basis`; pinned to the orderbook's market
+    #[account(mut, address = orderbook.load()?.market @ LikeliError::OrderbookMismatch)]
+    pub market: UncheckedAccount<'info>,
+
+    /// Vault authority PDA (signs for order vault transfers)
+    /// CHECK: This is a PDA controlled by the program
     #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8 + Orderbook::INIT_SPACE,
-        seeds = [b"orderbook", market.key().as_ref()],
+        seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
-    pub orderbook: Account<'info, Orderbook>,
-    
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Token account escrowing collateral for resting bids
     #[account(
-        init_if_needed,
-        payer = buyer,
-        space = 8 + MultiPosition::INIT_SPACE,
-        seeds = [b"multi_position", market.key().as_ref(), buyer.key().as_ref()],
-        bump
+        mut,
+        seeds = [ORDER_VAULT_SEED, market.key().as_ref()],
+        bump,
+        constraint = order_vault.owner == vault_authority.key()
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub order_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
 
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
     #[account(mut)]
-    pub buyer: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    pub market: Account<'info, Market>,
+    
+    pub resolver: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ConvertPositionsWithVault<'info> {
-    pub market: Account<'info, MultiMarket>,
+pub struct GetMarketPrice<'info> {
+    pub market: Account<'info, Market>,
+}
+
+#[derive(Accounts)]
+pub struct SetMarketFees<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
     
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeOutcome<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
     #[account(
-        mut,
-        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [PROPOSAL_SEED, market.key().as_ref()],
         bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub proposal: Account<'info, Proposal>,
 
-    /// Vault authority PDA (signs for vault transfers)
-    /// CHECK: Vault authority is a PDA
+    /// CHECK: This is a PDA controlled by the program
     #[account(
         seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
     pub vault_authority: UncheckedAccount<'info>,
-    
-    /// Vault's token account holding collateral
+
     #[account(
-        mut,
-        constraint = vault_ata.owner == vault_authority.key()
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
     )]
     pub vault_ata: Account<'info, TokenAccount>,
-    
-    /// User's token account to receive collateral
+
+    /// Token account holding bonds, created by the market's first proposal
     #[account(
-        mut,
-        constraint = user_ata.owner == owner.key()
+        init_if_needed,
+        payer = proposer,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [BOND_VAULT_SEED, market.key().as_ref()],
+        bump
     )]
-    pub user_ata: Account<'info, TokenAccount>,
-    
-    /// Fee vault's token account
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(address = vault_ata.mint)]
+    pub collateral_mint: Account<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [FEE_VAULT_SEED],
-        bump
+        constraint = proposer_ata.owner == proposer.key()
     )]
-    pub fee_vault_ata: Account<'info, TokenAccount>,
-    
+    pub proposer_ata: Account<'info, TokenAccount>,
+
     #[account(mut)]
-    pub owner: Signer<'info>,
-    
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct SplitPositionWithVault<'info> {
+pub struct ProposeAnswerOutcome<'info> {
     pub market: Account<'info, MultiMarket>,
-    
+
+    #[account(mut, constraint = answer.market == market.key())]
     pub answer: Account<'info, Answer>,
-    
+
     #[account(
-        mut,
-        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [PROPOSAL_SEED, answer.key().as_ref()],
         bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub proposal: Account<'info, Proposal>,
 
-    /// Vault authority PDA (signs for vault transfers)
-    /// CHECK: Vault authority is a PDA
+    /// CHECK: This is a PDA controlled by the program
     #[account(
         seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
     pub vault_authority: UncheckedAccount<'info>,
-    
-    /// Vault's token account holding collateral
+
     #[account(
-        mut,
-        constraint = vault_ata.owner == vault_authority.key()
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
     )]
     pub vault_ata: Account<'info, TokenAccount>,
-    
-    /// User's token account
+
+    /// Token account holding bonds, created by the market's first proposal
+    #[account(
+        init_if_needed,
+        payer = proposer,
+        token::mint = collateral_mint,
+        token::authority = vault_authority,
+        seeds = [BOND_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
+
+    #[account(address = vault_ata.mint)]
+    pub collateral_mint: Account<'info, Mint>,
+
     #[account(
         mut,
-        constraint = user_ata.owner == owner.key()
+        constraint = proposer_ata.owner == proposer.key()
     )]
-    pub user_ata: Account<'info, TokenAccount>,
-    
+    pub proposer_ata: Account<'info, TokenAccount>,
+
     #[account(mut)]
-    pub owner: Signer<'info>,
-    
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
 }
 
-// Keep old contexts for backwards compatibility
 #[derive(Accounts)]
-pub struct ConvertPositions<'info> {
-    pub market: Account<'info, MultiMarket>,
-    
+pub struct SetGlobalCommittee<'info> {
     #[account(
-        mut,
-        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        init_if_needed,
+        payer = authority,
+        space = 8 + Committee::INIT_SPACE,
+        seeds = [COMMITTEE_SEED],
         bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub committee: Account<'info, Committee>,
 
-    pub owner: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::LikeliContracts>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ LikeliError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct SplitPosition<'info> {
-    pub market: Account<'info, MultiMarket>,
-    
-    pub answer: Account<'info, Answer>,
-    
+pub struct SetMarketCommittee<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
     #[account(
-        mut,
-        seeds = [b"multi_position", market.key().as_ref(), owner.key().as_ref()],
+        init_if_needed,
+        payer = creator,
+        space = 8 + Committee::INIT_SPACE,
+        seeds = [COMMITTEE_SEED, market.key().as_ref()],
         bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub committee: Account<'info, Committee>,
 
-    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ResolveAnswer<'info> {
+pub struct SetMultiMarketCommittee<'info> {
     #[account(mut)]
     pub market: Account<'info, MultiMarket>,
-    
-    #[account(mut, constraint = answer.market == market.key())]
-    pub answer: Account<'info, Answer>,
-    
-    pub resolver: Signer<'info>,
-}
 
-#[derive(Accounts)]
-pub struct ClaimMultiWinnings<'info> {
-    #[account(constraint = market.resolved @ LikeliError::MarketNotResolved)]
-    pub market: Account<'info, MultiMarket>,
-    
     #[account(
-        mut,
-        seeds = [b"multi_position", market.key().as_ref(), claimer.key().as_ref()],
+        init_if_needed,
+        payer = creator,
+        space = 8 + Committee::INIT_SPACE,
+        seeds = [COMMITTEE_SEED, market.key().as_ref()],
         bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub committee: Account<'info, Committee>,
 
-    pub claimer: Signer<'info>,
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
-/// Claim multi-choice winnings with actual token transfer
 #[derive(Accounts)]
-pub struct ClaimMultiWinningsWithVault<'info> {
-    #[account(constraint = market.resolved @ LikeliError::MarketNotResolved)]
-    pub market: Account<'info, MultiMarket>,
-    
+pub struct DisputeProposal<'info> {
     #[account(
         mut,
-        seeds = [b"multi_position", market.key().as_ref(), claimer.key().as_ref()],
-        bump
+        seeds = [PROPOSAL_SEED, proposal.target.as_ref()],
+        bump = proposal.bump
     )]
-    pub position: Account<'info, MultiPosition>,
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Binary or multi-choice market, deserialized by type in the handler
+    #[account(address = proposal.market)]
+    pub market: UncheckedAccount<'info>,
+
+    /// Committee the dispute escalates to; the global one may not exist yet
+    /// CHECK: Address checked against the market's committee in the handler
+    pub committee: UncheckedAccount<'info>,
 
-    /// Vault authority PDA
-    /// CHECK: This is a PDA controlled by the program
     #[account(
-        seeds = [VAULT_SEED, market.key().as_ref()],
+        mut,
+        seeds = [BOND_VAULT_SEED, proposal.market.as_ref()],
         bump
     )]
-    pub vault_authority: UncheckedAccount<'info>,
-    
-    /// Vault's token account holding collateral
+    pub bond_vault: Account<'info, TokenAccount>,
+
     #[account(
         mut,
-        constraint = vault_ata.owner == vault_authority.key()
+        constraint = disputer_ata.owner == disputer.key()
     )]
-    pub vault_ata: Account<'info, TokenAccount>,
-    
-    /// Claimer's token account to receive payout
+    pub disputer_ata: Account<'info, TokenAccount>,
+
+    pub disputer: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct VoteOnDispute<'info> {
     #[account(
         mut,
-        constraint = claimer_ata.owner == claimer.key()
+        seeds = [PROPOSAL_SEED, proposal.target.as_ref()],
+        bump = proposal.bump
     )]
-    pub claimer_ata: Account<'info, TokenAccount>,
+    pub proposal: Account<'info, Proposal>,
 
-    #[account(mut)]
-    pub claimer: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
+    pub member: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceOrder<'info> {
+pub struct FinalizeProposal<'info> {
     #[account(mut)]
     pub market: Account<'info, Market>,
-    
+
     #[account(
-        init_if_needed,
-        payer = owner,
-        space = 8 + Orderbook::INIT_SPACE,
-        seeds = [b"orderbook", market.key().as_ref()],
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, market.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: This is a PDA controlled by the program
+    #[account(
+        seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
-    pub orderbook: Account<'info, Orderbook>,
-    
+    pub vault_authority: UncheckedAccount<'info>,
+
     #[account(
-        init,
-        payer = owner,
-        space = 8 + LimitOrder::INIT_SPACE,
+        mut,
+        seeds = [BOND_VAULT_SEED, market.key().as_ref()],
+        bump
     )]
-    pub order: Account<'info, LimitOrder>,
+    pub bond_vault: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    pub system_program: Program<'info, System>,
+    #[account(
+        mut,
+        constraint = proposer_ata.owner == proposal.proposer
+    )]
+    pub proposer_ata: Account<'info, TokenAccount>,
+
+    /// Required when the proposal was disputed and the disputer wins
+    #[account(
+        mut,
+        constraint = Some(disputer_ata.owner) == proposal.disputer
+    )]
+    pub disputer_ata: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the proposal account's rent
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct PlaceMultiOrder<'info> {
+pub struct FinalizeAnswerProposal<'info> {
     #[account(mut)]
     pub market: Account<'info, MultiMarket>,
-    
+
+    #[account(mut, constraint = answer.market == market.key())]
+    pub answer: Account<'info, Answer>,
+
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PROPOSAL_SEED, answer.key().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: This is a PDA controlled by the program
     #[account(
-        init_if_needed,
-        payer = owner,
-        space = 8 + Orderbook::INIT_SPACE,
-        seeds = [b"orderbook", market.key().as_ref()],
+        seeds = [VAULT_SEED, market.key().as_ref()],
         bump
     )]
-    pub orderbook: Account<'info, Orderbook>,
-    
+    pub vault_authority: UncheckedAccount<'info>,
+
+    /// Receives forfeited bonds
     #[account(
-        init,
-        payer = owner,
-        space = 8 + LimitOrder::INIT_SPACE,
+        mut,
+        seeds = [b"vault_ata", market.key().as_ref()],
+        bump
     )]
-    pub order: Account<'info, LimitOrder>,
+    pub vault_ata: Account<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    #[account(
+        mut,
+        seeds = [BOND_VAULT_SEED, market.key().as_ref()],
+        bump
+    )]
+    pub bond_vault: Account<'info, TokenAccount>,
 
-#[derive(Accounts)]
-pub struct CancelOrder<'info> {
     #[account(
         mut,
-        close = owner,
-        constraint = order.owner == owner.key() @ LikeliError::Unauthorized
+        constraint = proposer_ata.owner == proposal.proposer
     )]
-    pub order: Account<'info, LimitOrder>,
-    
+    pub proposer_ata: Account<'info, TokenAccount>,
+
+    /// Required when the proposal was disputed and the disputer wins
     #[account(
         mut,
-        seeds = [b"orderbook", order.market.as_ref()],
-        bump
+        constraint = Some(disputer_ata.owner) == proposal.disputer
     )]
-    pub orderbook: Account<'info, Orderbook>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    pub disputer_ata: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Receives the proposal account's rent
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    #[account(mut)]
+pub struct ResolveFromPriceFeed<'info> {
+    #[account(
+        mut,
+        constraint = market.price_feed != Pubkey::default() @ LikeliError::InvalidPriceFeed
+    )]
     pub market: Account<'info, Market>,
-    
-    pub resolver: Signer<'info>,
+
+    /// CHECK: Pyth-style price account, parsed in the handler
+    #[account(
+        address = market.price_feed @ LikeliError::InvalidPriceFeed,
+        owner = market.price_feed_owner @ LikeliError::InvalidPriceFeed
+    )]
+    pub price_feed: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct GetMarketPrice<'info> {
+pub struct CancelAbandonedMarket<'info> {
+    #[account(mut)]
     pub market: Account<'info, Market>,
 }
 
 #[derive(Accounts)]
-pub struct SetMarketFees<'info> {
+pub struct CancelAbandonedMultiMarket<'info> {
     #[account(mut)]
-    pub market: Account<'info, Market>,
-    
-    pub creator: Signer<'info>,
+    pub market: Account<'info, MultiMarket>,
 }
 
 // ============== STATE ACCOUNTS ==============
@@ -1914,14 +5239,25 @@ pub struct SetMarketFees<'info> {
 #[derive(InitSpace)]
 pub struct Market {
     pub creator: Pubkey,
+    pub resolver: Pubkey,           // Resolves the market; the creator unless handed off
     #[max_len(200)]
     pub question: String,
     pub resolution_time: i64,
+    pub close_time: i64,            // Trading halts here; defaults to resolution_time
     pub yes_pool: u64,
     pub no_pool: u64,
+    pub total_lp_shares: u64,       // Claims on the pools, held in LpPositions
     pub total_volume: u64,
     pub resolved: bool,
-    pub outcome: bool,
+    pub outcome: Option<MarketOutcome>,
+    pub resolution_bps: u16,        // YES payout per share once resolved (bps)
+    pub proposal_pending: bool,     // An oracle proposal is open; blocks direct resolution
+    // Price-feed resolution: YES if the feed is above strike × 10^strike_expo
+    pub price_feed: Pubkey,         // Default = resolved by the resolver or oracle
+    pub price_feed_owner: Pubkey,   // Program that must own the price account
+    pub strike_price: i64,
+    pub strike_expo: i32,
+    pub max_staleness: i64,         // Max seconds after resolution time the price may be published
     pub created_at: i64,
     pub bump: u8,
     // Multi-choice group support
@@ -1935,6 +5271,17 @@ pub struct Market {
     pub platform_fee_bps: u16,
     pub liquidity_fee_bps: u16,
     pub collected_fees: u64,
+    pub orderbook: Pubkey,          // Default until create_orderbook
+    // Orderbook rules
+    pub tick_size: u16,             // Order prices must be a multiple (bps)
+    pub min_order_qty: u64,
+    // Optimistic oracle
+    pub oracle_bond: u64,           // Collateral posted by proposers and disputers
+    pub challenge_window: i64,      // Seconds a proposal stays disputable
+    pub committee: Pubkey,          // Dispute committee, default = global committee
+    pub resolution_grace: i64,      // Seconds after resolution_time before anyone may cancel
+    pub abandoned: bool,            // Cancelled N/A after the grace period
+    pub refund_basis: u64,          // Sum of positive cost bases, sharing the vault if resolved N/A
 }
 
 /// Multi-choice market
@@ -1942,16 +5289,99 @@ pub struct Market {
 #[derive(InitSpace)]
 pub struct MultiMarket {
     pub creator: Pubkey,
+    pub resolver: Pubkey,           // Resolves answers; the creator unless handed off
     pub question_hash: [u8; 32],
     pub answer_count: u8,
     pub is_one_winner: bool,        // true = NegRisk enabled
     pub volume: u64,
     pub fee_bps: u16,
     pub resolution_time: i64,
+    pub close_time: i64,            // Trading halts here; defaults to resolution_time
     pub resolved: bool,
     pub created_at: i64,
     pub bump: u8,
     pub answers_resolved: u8,
+    pub winning_answer: Option<u8>, // Set once a one-winner market resolves an answer YES
+    pub resolved_mask: u16,         // Bit i set once answer i resolves
+    pub yes_mask: u16,              // Bit i set if answer i resolved YES
+    pub invalid_mask: u16,          // Bit i set if answer i resolved N/A
+    pub orderbooks: [Pubkey; 10],   // Per-answer orderbooks, default until created
+    // Orderbook rules
+    pub tick_size: u16,             // Order prices must be a multiple (bps)
+    pub min_order_qty: u64,
+    // Optimistic oracle
+    pub oracle_bond: u64,           // Collateral posted by proposers and disputers
+    pub challenge_window: i64,      // Seconds a proposal stays disputable
+    pub committee: Pubkey,          // Dispute committee, default = global committee
+    pub resolution_grace: i64,      // Seconds after resolution_time before anyone may cancel
+    pub abandoned: bool,            // Cancelled N/A after the grace period
+    // Pricing
+    pub pricing: PricingEngine,
+    pub lmsr_b: u64,                // LMSR liquidity parameter; max loss is b·ln(N)
+    pub lmsr_q: [i64; 10],          // Net YES shares the LMSR has sold per answer
+    // N/A refunds
+    pub refund_basis: [u64; 10],    // Sum of positive cost bases per answer
+    pub answer_collateral: [u64; 10], // Vault collateral paid in through each answer and not yet paid out
+}
+
+impl MultiMarket {
+    /// Record an answer's outcome in the resolution bitmaps
+    pub fn record_outcome(&mut self, index: u8, outcome: MarketOutcome) {
+        let bit = 1u16 << index;
+        self.resolved_mask |= bit;
+        match outcome {
+            MarketOutcome::Yes => self.yes_mask |= bit,
+            MarketOutcome::Invalid => self.invalid_mask |= bit,
+            // Answers never resolve to a probability
+            MarketOutcome::No | MarketOutcome::Probability(_) => {}
+        }
+        self.answers_resolved = self.answers_resolved.checked_add(1).unwrap();
+    }
+
+    /// Outcome of an answer, if resolved
+    pub fn answer_outcome(&self, index: u8) -> Option<MarketOutcome> {
+        let bit = 1u16 << index;
+        if self.resolved_mask & bit == 0 {
+            None
+        } else if self.invalid_mask & bit != 0 {
+            Some(MarketOutcome::Invalid)
+        } else if self.yes_mask & bit != 0 {
+            Some(MarketOutcome::Yes)
+        } else {
+            Some(MarketOutcome::No)
+        }
+    }
+
+    /// Record collateral entering the vault through an answer
+    pub fn deposit_collateral(&mut self, index: u8, amount: u64) {
+        let held = &mut self.answer_collateral[index as usize];
+        *held = held.checked_add(amount).unwrap();
+    }
+
+    /// Record collateral leaving the vault through an answer
+    pub fn withdraw_collateral(&mut self, index: u8, amount: u64) {
+        let held = &mut self.answer_collateral[index as usize];
+        *held = held.saturating_sub(amount);
+    }
+
+    /// Collateral an N/A answer refunds and the positive cost basis it is
+    /// shared across. A one-winner market is cancelled as a whole, so its
+    /// refunds share the entire vault across every answer's bases.
+    pub fn refund_pool(&self, index: u8, vault_balance: u64) -> (u64, u64) {
+        if self.is_one_winner {
+            (vault_balance, self.refund_basis.iter().sum())
+        } else {
+            let idx = index as usize;
+            (self.answer_collateral[idx].min(vault_balance), self.refund_basis[idx])
+        }
+    }
+}
+
+/// Market maker pricing a multi-choice market's answers
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PricingEngine {
+    Cpmm,   // Per-answer pools, kept in line by sync_sibling_pools
+    Lmsr,   // Logarithmic market scoring rule across all answers
 }
 
 /// Answer in a multi-choice market
@@ -1963,9 +5393,65 @@ pub struct Answer {
     pub label_hash: [u8; 32],
     pub yes_pool: u64,
     pub no_pool: u64,
+    pub total_lp_shares: u64,       // Claims on the pools, held in LpPositions
     pub volume: u64,
     pub resolved: bool,
-    pub outcome: Option<bool>,
+    pub outcome: Option<MarketOutcome>,
+    pub proposal_pending: bool,     // An oracle proposal is open; blocks direct resolution
+}
+
+/// Optimistic-oracle proposal for a binary market or a multi-choice answer
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub market: Pubkey,
+    pub target: Pubkey,             // The market itself, or the answer being resolved
+    pub proposer: Pubkey,
+    pub outcome: MarketOutcome,
+    pub bond: u64,                  // Posted by the proposer, matched by a disputer
+    pub challenge_ends: i64,
+    pub disputer: Option<Pubkey>,
+    pub bump: u8,
+    // Committee arbitration, snapshotted when disputed
+    #[max_len(MAX_COMMITTEE_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub votes: [Option<MarketOutcome>; MAX_COMMITTEE_MEMBERS], // By committee member index
+    pub ruling: Option<MarketOutcome>,
+    pub voting_ends: i64,           // Disputes without a ruling by then lapse to N/A
+}
+
+impl Proposal {
+    /// Disputed without a ruling; final once `voting_ends` has passed
+    pub fn lapsed(&self) -> bool {
+        self.disputer.is_some() && self.ruling.is_none()
+    }
+
+    /// Bonds held for this proposal, all paid to the winning side
+    pub fn total_bond(&self) -> u64 {
+        if self.disputer.is_some() { self.bond * 2 } else { self.bond }
+    }
+}
+
+/// M-of-N committee arbitrating disputed proposals
+#[account]
+#[derive(InitSpace)]
+pub struct Committee {
+    #[max_len(MAX_COMMITTEE_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,              // Votes needed for a ruling, a strict majority
+    pub bump: u8,
+}
+
+/// How a market or answer resolved
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum MarketOutcome {
+    Yes,
+    No,
+    /// N/A: the market is cancelled and traders are refunded their cost basis
+    Invalid,
+    /// MKT: YES shares pay this many bps, NO shares the remainder (binary markets only)
+    Probability(u16),
 }
 
 /// User position in binary market
@@ -1976,47 +5462,578 @@ pub struct UserPosition {
     pub market: Pubkey,
     pub yes_shares: u64,
     pub no_shares: u64,
+    // Shares locked by resting asks
+    pub locked_yes_shares: u64,
+    pub locked_no_shares: u64,
+    // Net collateral paid in, refunded if the market resolves N/A.
+    // Negative once more has been taken out than paid in.
+    pub cost_basis: i64,
+}
+
+impl UserPosition {
+    /// Move free shares into the locked balance backing a resting ask
+    pub fn lock_shares(&mut self, is_yes: bool, qty: u64) -> Result<()> {
+        let (free, locked) = if is_yes {
+            (&mut self.yes_shares, &mut self.locked_yes_shares)
+        } else {
+            (&mut self.no_shares, &mut self.locked_no_shares)
+        };
+        require!(*free >= qty, LikeliError::InsufficientShares);
+        *free -= qty;
+        *locked = locked.checked_add(qty).unwrap();
+        Ok(())
+    }
+
+    /// Return locked shares to the free balance
+    pub fn unlock_shares(&mut self, is_yes: bool, qty: u64) {
+        let (free, locked) = if is_yes {
+            (&mut self.yes_shares, &mut self.locked_yes_shares)
+        } else {
+            (&mut self.no_shares, &mut self.locked_no_shares)
+        };
+        *locked = locked.checked_sub(qty).unwrap();
+        *free = free.checked_add(qty).unwrap();
+    }
+
+    pub fn free_shares(&self, is_yes: bool) -> u64 {
+        if is_yes { self.yes_shares } else { self.no_shares }
+    }
+
+    pub fn add_shares(&mut self, is_yes: bool, qty: u64) {
+        let free = if is_yes { &mut self.yes_shares } else { &mut self.no_shares };
+        *free = free.checked_add(qty).unwrap();
+    }
+
+    pub fn remove_shares(&mut self, is_yes: bool, qty: u64) -> Result<()> {
+        let free = if is_yes { &mut self.yes_shares } else { &mut self.no_shares };
+        require!(*free >= qty, LikeliError::InsufficientShares);
+        *free -= qty;
+        Ok(())
+    }
+
+    /// Apply a maker fill: bids receive shares, asks give up locked shares
+    pub fn settle_fill(&mut self, is_yes: bool, is_bid: bool, qty: u64) {
+        if is_bid {
+            self.add_shares(is_yes, qty);
+        } else {
+            let locked = if is_yes { &mut self.locked_yes_shares } else { &mut self.locked_no_shares };
+            *locked = locked.checked_sub(qty).unwrap();
+        }
+    }
+
+    /// Record collateral paid into the market
+    pub fn add_cost_basis(&mut self, amount: u64, refund_basis: &mut u64) {
+        shift_cost_basis(&mut self.cost_basis, i64::try_from(amount).unwrap(), refund_basis);
+    }
+
+    /// Record collateral paid out of the market
+    pub fn reduce_cost_basis(&mut self, amount: u64, refund_basis: &mut u64) {
+        shift_cost_basis(&mut self.cost_basis, -i64::try_from(amount).unwrap(), refund_basis);
+    }
+
+    /// Clear the cost basis, returning the part an N/A refund is weighted by
+    pub fn take_refund_weight(&mut self, refund_basis: &mut u64) -> u64 {
+        let basis = self.cost_basis;
+        shift_cost_basis(&mut self.cost_basis, -basis, refund_basis);
+        basis.max(0) as u64
+    }
+
+    /// Collateral owed for the free balance once the market resolves with
+    /// shares; N/A refunds are shared out by `refund_share`
+    pub fn payout(&self, outcome: MarketOutcome) -> u64 {
+        match outcome {
+            MarketOutcome::Yes => self.yes_shares,
+            MarketOutcome::No => self.no_shares,
+            MarketOutcome::Invalid => 0,
+            MarketOutcome::Probability(bps) => probability_payout(self.yes_shares, self.no_shares, bps),
+        }
+    }
+}
+
+/// Liquidity provider's share of a binary market's or an answer's pools
+#[account]
+#[derive(InitSpace)]
+pub struct LpPosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,             // Market, or Answer for multi-choice pools
+    pub shares: u64,
+    pub bump: u8,
+}
+
+/// User position in multi-choice market
+#[account]
+#[derive(InitSpace)]
+pub struct MultiPosition {
+    pub owner: Pubkey,
+    pub market: Pubkey,
+    pub yes_shares: [u64; 10],      // Max 10 answers
+    pub no_shares: [u64; 10],
+    // Shares locked by resting asks
+    pub locked_yes_shares: [u64; 10],
+    pub locked_no_shares: [u64; 10],
+    // Net collateral paid in per answer, refunded if the answer resolves N/A.
+    // Negative once more has been taken out than paid in.
+    pub cost_basis: [i64; 10],
+}
+
+impl MultiPosition {
+    /// Move free shares into the locked balance backing a resting ask
+    pub fn lock_shares(&mut self, index: u8, is_yes: bool, qty: u64) -> Result<()> {
+        let idx = index as usize;
+        let (free, locked) = if is_yes {
+            (&mut self.yes_shares[idx], &mut self.locked_yes_shares[idx])
+        } else {
+            (&mut self.no_shares[idx], &mut self.locked_no_shares[idx])
+        };
+        require!(*free >= qty, LikeliError::InsufficientShares);
+        *free -= qty;
+        *locked = locked.checked_add(qty).unwrap();
+        Ok(())
+    }
+
+    /// Return locked shares to the free balance
+    pub fn unlock_shares(&mut self, index: u8, is_yes: bool, qty: u64) {
+        let idx = index as usize;
+        let (free, locked) = if is_yes {
+            (&mut self.yes_shares[idx], &mut self.locked_yes_shares[idx])
+        } else {
+            (&mut self.no_shares[idx], &mut self.locked_no_shares[idx])
+        };
+        *locked = locked.checked_sub(qty).unwrap();
+        *free = free.checked_add(qty).unwrap();
+    }
+
+    pub fn free_shares(&self, index: u8, is_yes: bool) -> u64 {
+        let idx = index as usize;
+        if is_yes { self.yes_shares[idx] } else { self.no_shares[idx] }
+    }
+
+    pub fn add_shares(&mut self, index: u8, is_yes: bool, qty: u64) {
+        let idx = index as usize;
+        let free = if is_yes { &mut self.yes_shares[idx] } else { &mut self.no_shares[idx] };
+        *free = free.checked_add(qty).unwrap();
+    }
+
+    pub fn remove_shares(&mut self, index: u8, is_yes: bool, qty: u64) -> Result<()> {
+        let idx = index as usize;
+        let free = if is_yes { &mut self.yes_shares[idx] } else { &mut self.no_shares[idx] };
+        require!(*free >= qty, LikeliError::InsufficientShares);
+        *free -= qty;
+        Ok(())
+    }
+
+    /// Apply a maker fill: bids receive shares, asks give up locked shares
+    pub fn settle_fill(&mut self, index: u8, is_yes: bool, is_bid: bool, qty: u64) {
+        if is_bid {
+            self.add_shares(index, is_yes, qty);
+        } else {
+            let idx = index as usize;
+            let locked = if is_yes { &mut self.locked_yes_shares[idx] } else { &mut self.locked_no_shares[idx] };
+            *locked = locked.checked_sub(qty).unwrap();
+        }
+    }
+
+    /// Record collateral paid into an answer
+    pub fn add_cost_basis(&mut self, index: u8, amount: u64, refund_basis: &mut u64) {
+        shift_cost_basis(&mut self.cost_basis[index as usize], i64::try_from(amount).unwrap(), refund_basis);
+    }
+
+    /// Record collateral paid out of an answer
+    pub fn reduce_cost_basis(&mut self, index: u8, amount: u64, refund_basis: &mut u64) {
+        shift_cost_basis(&mut self.cost_basis[index as usize], -i64::try_from(amount).unwrap(), refund_basis);
+    }
+
+    /// Clear an answer's cost basis, returning the part an N/A refund is weighted by
+    pub fn take_refund_weight(&mut self, index: u8, refund_basis: &mut u64) -> u64 {
+        let basis = self.cost_basis[index as usize];
+        shift_cost_basis(&mut self.cost_basis[index as usize], -basis, refund_basis);
+        basis.max(0) as u64
+    }
+
+    /// Collateral owed for the free balance of an answer resolved with
+    /// shares; N/A refunds are shared out by `refund_share`
+    pub fn payout(&self, index: u8, outcome: MarketOutcome) -> u64 {
+        let idx = index as usize;
+        match outcome {
+            MarketOutcome::Yes => self.yes_shares[idx],
+            MarketOutcome::No => self.no_shares[idx],
+            MarketOutcome::Invalid => 0,
+            MarketOutcome::Probability(bps) => probability_payout(self.yes_shares[idx], self.no_shares[idx], bps),
+        }
+    }
+}
+
+/// Maximum resting orders on each side of an orderbook
+pub const ORDERS_PER_SIDE: usize = 2048;
+/// Maximum distinct owners with resting orders or unsettled fills in an orderbook
+pub const MAX_BOOK_OWNERS: usize = 512;
+
+/// Resting order stored inline in an orderbook side
+#[zero_copy]
+pub struct BookOrder {
+    pub owner: Pubkey,
+    pub seq: u64,                   // Order id, increasing with placement time
+    pub qty: u64,                   // Unfilled shares
+    pub expires_at: i64,            // 0 = good till cancelled
+    pub price: u32,                 // bps
+    pub owner_slot: u32,            // Index of the owner's balance in the orderbook
+}
+
+impl BookOrder {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// One side of an orderbook, sorted from worst to best price so the best
+/// order sits at the end; equal prices keep placement order (price-time priority)
+#[zero_copy]
+pub struct BookSide {
+    pub len: u64,
+    pub orders: [BookOrder; ORDERS_PER_SIDE],
+}
+
+impl BookSide {
+    pub fn orders(&self) -> &[BookOrder] {
+        &self.orders[..self.len as usize]
+    }
+
+    pub fn best_mut(&mut self) -> Option<&mut BookOrder> {
+        let len = self.len as usize;
+        len.checked_sub(1).map(|i| &mut self.orders[i])
+    }
+
+    pub fn find(&self, seq: u64) -> Option<usize> {
+        self.orders().iter().position(|o| o.seq == seq)
+    }
+
+    /// Insert an order behind every resting order with an equal or better price
+    pub fn insert(&mut self, order: BookOrder, is_bid: bool) -> Result<()> {
+        let len = self.len as usize;
+        require!(len < ORDERS_PER_SIDE, LikeliError::OrderbookFull);
+
+        // Bids ascend and asks descend towards the best price
+        let pos = self.orders[..len].partition_point(|o| {
+            if is_bid { o.price < order.price } else { o.price > order.price }
+        });
+        self.orders.copy_within(pos..len, pos + 1);
+        self.orders[pos] = order;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, pos: usize) -> BookOrder {
+        let len = self.len as usize;
+        let order = self.orders[pos];
+        self.orders.copy_within(pos + 1..len, pos);
+        self.len -= 1;
+        order
+    }
+}
+
+/// An owner's balances accrued in an orderbook, claimed with `settle_funds`
+#[zero_copy]
+pub struct BookBalance {
+    pub owner: Pubkey,              // Default = free slot
+    pub open_orders: u64,
+    pub resting: u64,               // Collateral value of the owner's resting orders
+    pub collateral: u64,            // Ask proceeds and released bid escrow
+    pub yes_bought: u64,            // Shares bought by resting bids
+    pub no_bought: u64,
+    pub yes_sold: u64,              // Locked shares sold by resting asks
+    pub no_sold: u64,
+    pub yes_unlocked: u64,          // Locked shares released by removed asks
+    pub no_unlocked: u64,
+    pub bid_cost: u64,              // Escrow spent by filled bids, added to the cost basis
+    pub ask_proceeds: u64,          // Part of `collateral` earned by filled asks
+}
+
+impl BookBalance {
+    /// Record a maker fill: bids buy shares, asks sell locked shares for collateral
+    pub fn credit_fill(&mut self, is_yes: bool, is_bid: bool, qty: u64, cost: u64) {
+        let shares = match (is_yes, is_bid) {
+            (true, true) => &mut self.yes_bought,
+            (false, true) => &mut self.no_bought,
+            (true, false) => &mut self.yes_sold,
+            (false, false) => &mut self.no_sold,
+        };
+        *shares = shares.checked_add(qty).unwrap();
+        if is_bid {
+            self.bid_cost = self.bid_cost.checked_add(cost).unwrap();
+        } else {
+            self.collateral = self.collateral.checked_add(cost).unwrap();
+            self.ask_proceeds = self.ask_proceeds.checked_add(cost).unwrap();
+        }
+    }
+
+    /// Release the escrow backing a removed order's remainder:
+    /// collateral for bids, locked shares for asks
+    pub fn release(&mut self, order: &BookOrder, is_yes: bool, is_bid: bool) {
+        self.open_orders -= 1;
+        let escrow = order_notional(order.price as u64, order.qty);
+        self.resting -= escrow;
+        self.release_escrow(is_yes, is_bid, order.qty, escrow);
+    }
+
+    /// Release escrow backing `qty` shares of a resting order: `collateral`
+    /// for bids, the locked shares themselves for asks
+    pub fn release_escrow(&mut self, is_yes: bool, is_bid: bool, qty: u64, collateral: u64) {
+        if is_bid {
+            self.collateral = self.collateral.checked_add(collateral).unwrap();
+        } else if is_yes {
+            self.yes_unlocked = self.yes_unlocked.checked_add(qty).unwrap();
+        } else {
+            self.no_unlocked = self.no_unlocked.checked_add(qty).unwrap();
+        }
+    }
+
+    pub fn has_unsettled(&self) -> bool {
+        self.collateral > 0
+            || self.yes_bought > 0
+            || self.no_bought > 0
+            || self.yes_sold > 0
+            || self.no_sold > 0
+            || self.yes_unlocked > 0
+            || self.no_unlocked > 0
+    }
+
+    pub fn clear_settled(&mut self) {
+        self.collateral = 0;
+        self.yes_bought = 0;
+        self.no_bought = 0;
+        self.yes_sold = 0;
+        self.no_sold = 0;
+        self.yes_unlocked = 0;
+        self.no_unlocked = 0;
+        self.bid_cost = 0;
+        self.ask_proceeds = 0;
+    }
+
+    /// Whether the owner has nothing resting or waiting to settle
+    pub fn is_idle(&self) -> bool {
+        self.open_orders == 0 && !self.has_unsettled()
+    }
+}
+
+/// Zero-copy orderbook for a binary market or one answer of a multi-choice market
+/// Orders are stored inline, so matching needs no order accounts. Too large for
+/// a PDA: the client creates the account with `Orderbook::SPACE` bytes.
+#[account(zero_copy)]
+pub struct Orderbook {
+    pub market: Pubkey,
+    pub next_seq: u64,
+    pub answer_index: u8,           // Multi-choice books only
+    pub is_multi: u8,
+    pub _padding: [u8; 6],
+    pub sides: [BookSide; 4],       // YES bids, YES asks, NO bids, NO asks
+    pub balances: [BookBalance; MAX_BOOK_OWNERS],
+}
+
+fn side_index(is_yes: bool, is_bid: bool) -> usize {
+    match (is_yes, is_bid) {
+        (true, true) => 0,
+        (true, false) => 1,
+        (false, true) => 2,
+        (false, false) => 3,
+    }
 }
 
-/// User position in multi-choice market
-#[account]
-#[derive(InitSpace)]
-pub struct MultiPosition {
-    pub owner: Pubkey,
-    pub market: Pubkey,
-    pub yes_shares: [u64; 10],      // Max 10 answers
-    pub no_shares: [u64; 10],
-}
+impl Orderbook {
+    pub const SPACE: usize = 8 + std::mem::size_of::<Orderbook>();
+
+    pub fn side_mut(&mut self, is_yes: bool, is_bid: bool) -> &mut BookSide {
+        &mut self.sides[side_index(is_yes, is_bid)]
+    }
+
+    /// Find the taker's best counterparty: the best order on the opposite side
+    /// of the same outcome, or on the same side of the other outcome. Expired
+    /// orders at the top of either side are evicted on the way.
+    /// Returns whether the complementary side wins and the taker's price there.
+    pub fn best_source(&mut self, taker: &TakerOrder, now: i64) -> Option<(bool, u64)> {
+        let mut best: Option<(bool, u64, u64)> = None;
+
+        for is_complement in [false, true] {
+            let maker_is_yes = taker.is_yes != is_complement;
+            let maker_is_bid = taker.is_buy == is_complement;
+            let side = &mut self.sides[side_index(maker_is_yes, maker_is_bid)];
+
+            while let Some(expired) = side.best_mut().filter(|o| o.is_expired(now)).copied() {
+                side.len -= 1;
+                self.balances[expired.owner_slot as usize].release(&expired, maker_is_yes, maker_is_bid);
+            }
+
+            let Some(order) = side.orders().last() else { continue };
+            let price = if is_complement { 10000 - order.price as u64 } else { order.price as u64 };
+            let better = match best {
+                None => true,
+                Some((_, best_price, _)) if price != best_price => {
+                    if taker.is_buy { price < best_price } else { price > best_price }
+                }
+                Some((_, _, best_seq)) => order.seq < best_seq,
+            };
+            if better {
+                best = Some((is_complement, price, order.seq));
+            }
+        }
+
+        best.map(|(is_complement, price, _)| (is_complement, price))
+    }
+
+    pub fn find_owner(&self, owner: &Pubkey) -> Option<usize> {
+        self.balances.iter().position(|b| b.owner == *owner)
+    }
+
+    /// Occupied owner slot with the least collateral in resting orders, the
+    /// one `evict_book_owner` frees when the book is full
+    pub fn smallest_owner(&self) -> Option<usize> {
+        (0..MAX_BOOK_OWNERS)
+            .filter(|&slot| self.balances[slot].owner != Pubkey::default())
+            .min_by_key(|&slot| self.balances[slot].resting)
+    }
+
+    fn claim_owner_slot(&mut self, owner: &Pubkey) -> Result<usize> {
+        if let Some(slot) = self.find_owner(owner) {
+            return Ok(slot);
+        }
+        let slot = self.find_owner(&Pubkey::default()).ok_or(LikeliError::BookOwnersFull)?;
+        self.balances[slot].owner = *owner;
+        Ok(slot)
+    }
+
+    /// Rest an order in the book, returning its id
+    pub fn rest_order(
+        &mut self,
+        owner: &Pubkey,
+        is_yes: bool,
+        is_bid: bool,
+        price: u64,
+        qty: u64,
+        expires_at: i64,
+    ) -> Result<u64> {
+        let owner_slot = self.claim_owner_slot(owner)?;
+        let seq = self.next_seq;
+        self.side_mut(is_yes, is_bid).insert(
+            BookOrder {
+                owner: *owner,
+                seq,
+                qty,
+                expires_at,
+                price: price as u32,
+                owner_slot: owner_slot as u32,
+            },
+            is_bid,
+        )?;
+        self.next_seq += 1;
+        let balance = &mut self.balances[owner_slot];
+        balance.open_orders += 1;
+        balance.resting = balance.resting.checked_add(order_notional(price, qty)).unwrap();
+        Ok(seq)
+    }
+
+    /// Remove an owner's resting order and release its escrow into their balance
+    pub fn cancel_order(&mut self, owner: &Pubkey, is_yes: bool, is_bid: bool, order_id: u64) -> Result<BookOrder> {
+        let side = self.side_mut(is_yes, is_bid);
+        let pos = side.find(order_id).ok_or(LikeliError::OrderNotFound)?;
+        require_keys_eq!(side.orders[pos].owner, *owner, LikeliError::Unauthorized);
+
+        let order = side.remove(pos);
+        self.balances[order.owner_slot as usize].release(&order, is_yes, is_bid);
+        Ok(order)
+    }
+
+    /// Change a resting order's price and size. Returns the order as it was
+    /// and its id afterwards: only a size reduction at the same price keeps
+    /// the order's place (and id) in the queue.
+    pub fn amend_order(
+        &mut self,
+        owner: &Pubkey,
+        is_yes: bool,
+        is_bid: bool,
+        order_id: u64,
+        price: u64,
+        qty: u64,
+    ) -> Result<(BookOrder, u64)> {
+        let seq = self.next_seq;
+        let side = self.side_mut(is_yes, is_bid);
+        let pos = side.find(order_id).ok_or(LikeliError::OrderNotFound)?;
+        let old = side.orders[pos];
+        require_keys_eq!(old.owner, *owner, LikeliError::Unauthorized);
+
+        let keeps_place = price == old.price as u64 && qty <= old.qty;
+        if keeps_place {
+            side.orders[pos].qty = qty;
+        } else {
+            side.remove(pos);
+            side.insert(
+                BookOrder {
+                    seq,
+                    qty,
+                    price: price as u32,
+                    ..old
+                },
+                is_bid,
+            )?;
+            self.next_seq += 1;
+        }
+
+        let balance = &mut self.balances[old.owner_slot as usize];
+        balance.resting = (balance.resting - order_notional(old.price as u64, old.qty))
+            .checked_add(order_notional(price, qty))
+            .unwrap();
+        Ok((old, if keeps_place { order_id } else { seq }))
+    }
+
+    /// Remove all of an owner's resting orders, releasing their escrow into
+    /// their balance. Returns the number of orders cancelled.
+    pub fn cancel_all(&mut self, owner: &Pubkey) -> u64 {
+        let Some(slot) = self.find_owner(owner) else {
+            return 0;
+        };
+        let open_orders = self.balances[slot].open_orders;
+        self.remove_where(|o| o.owner == *owner, open_orders)
+    }
+
+    /// Remove every expired order, releasing its escrow to the owner's balance.
+    /// Returns the number of orders evicted.
+    pub fn evict_expired(&mut self, now: i64) -> u64 {
+        self.remove_where(|o| o.is_expired(now), u64::MAX)
+    }
+
+    /// Remove matching orders, stopping once `limit` are gone so a known
+    /// number of orders doesn't cost a scan of the whole book
+    fn remove_where(&mut self, mut remove: impl FnMut(&BookOrder) -> bool, limit: u64) -> u64 {
+        let Orderbook { sides, balances, .. } = self;
+        let mut removed = 0;
 
-/// Limit order
-#[account]
-#[derive(InitSpace)]
-pub struct LimitOrder {
-    pub owner: Pubkey,
-    pub market: Pubkey,
-    pub answer_index: Option<u8>,   // None for binary, Some(idx) for multi-choice
-    pub price: u64,
-    pub qty: u64,
-    pub filled_qty: u64,
-    pub is_yes: bool,
-    pub is_bid: bool,
-    pub created_at: i64,
-    pub expires_at: Option<i64>,
-}
+        for (index, side) in sides.iter_mut().enumerate() {
+            if removed == limit {
+                break;
+            }
+            let (is_yes, is_bid) = (index < 2, index % 2 == 0);
+            let len = side.len as usize;
+            let mut kept = 0;
+            for i in 0..len {
+                if removed == limit {
+                    side.orders.copy_within(i..len, kept);
+                    kept += len - i;
+                    break;
+                }
+                let order = side.orders[i];
+                if remove(&order) {
+                    balances[order.owner_slot as usize].release(&order, is_yes, is_bid);
+                    removed += 1;
+                } else {
+                    if kept != i {
+                        side.orders[kept] = order;
+                    }
+                    kept += 1;
+                }
+            }
+            side.len = kept as u64;
+        }
 
-/// Orderbook for a market (size optimized for 10KB limit)
-#[account]
-#[derive(InitSpace)]
-pub struct Orderbook {
-    pub market: Pubkey,
-    #[max_len(50)]
-    pub yes_buy_orders: Vec<Pubkey>,
-    #[max_len(50)]
-    pub yes_sell_orders: Vec<Pubkey>,
-    #[max_len(50)]
-    pub no_buy_orders: Vec<Pubkey>,
-    #[max_len(50)]
-    pub no_sell_orders: Vec<Pubkey>,
+        removed
+    }
 }
 
 // ============== ERRORS ==============
@@ -2043,7 +6060,7 @@ pub enum LikeliError {
     InvalidPrice,
     #[msg("Insufficient shares")]
     InsufficientShares,
-    #[msg("Orderbook is full (max 100 orders per side)")]
+    #[msg("Orderbook is full (max 2048 orders per side)")]
     OrderbookFull,
     #[msg("Order not found in orderbook")]
     OrderNotFound,
@@ -2067,8 +6084,1315 @@ pub enum LikeliError {
     AnswerAlreadyResolved,
     #[msg("A winner has already been declared for this market")]
     WinnerAlreadyDeclared,
+    #[msg("One-winner mode can't change once the market has traded or resolved an answer")]
+    OneWinnerLocked,
     #[msg("Trade too large - max 25% of pool per trade")]
     TradeTooLarge,
     #[msg("Missing sibling accounts for rebalancing")]
     MissingSiblings,
+    #[msg("Position account required for this order")]
+    MissingPosition,
+    #[msg("Order has no unsettled fills")]
+    NothingToSettle,
+    #[msg("Orderbook does not belong to this market")]
+    OrderbookMismatch,
+    #[msg("Orderbook account required for this market")]
+    MissingOrderbook,
+    #[msg("Orderbook already created")]
+    OrderbookExists,
+    #[msg("Orderbook has no free owner slots")]
+    BookOwnersFull,
+    #[msg("Voting on this dispute has closed")]
+    VotingClosed,
+    #[msg("Set a non-zero oracle bond before proposals")]
+    OracleBondRequired,
+    #[msg("Only the resolver may propose this soon after the resolution time")]
+    ResolverWindowOpen,
+    #[msg("Orderbook still has free owner slots")]
+    BookOwnersAvailable,
+    #[msg("Cancel and settle orderbook orders before claiming a refund")]
+    UnsettledOrders,
+    #[msg("Liquidity in an N/A market is refunded through the cost basis")]
+    LiquidityRefunded,
+    #[msg("One-winner pools pay out once the market resolves")]
+    LiquidityLocked,
+    #[msg("Account does not belong to the order owner")]
+    OwnerAccountMismatch,
+    #[msg("Order expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("No expired orders to evict")]
+    NoExpiredOrders,
+    #[msg("Post-only order would cross the book")]
+    PostOnlyWouldCross,
+    #[msg("Fill-or-kill order could not be filled in full")]
+    OrderNotFilled,
+    #[msg("No resting orders within the price bound")]
+    NoLiquidity,
+    #[msg("Amended order would cross the book")]
+    AmendWouldCross,
+    #[msg("Price is not a multiple of the market tick size")]
+    InvalidTickSize,
+    #[msg("Order is below the market minimum size")]
+    OrderTooSmall,
+    #[msg("Answer does not belong to this market")]
+    AnswerMarketMismatch,
+    #[msg("One-winner markets can only be resolved N/A as a whole")]
+    PartialInvalidResolution,
+    #[msg("Invalid resolution probability")]
+    InvalidProbability,
+    #[msg("An oracle proposal is pending")]
+    ProposalPending,
+    #[msg("Challenge window must be positive")]
+    InvalidChallengeWindow,
+    #[msg("Proposal already disputed")]
+    ProposalDisputed,
+    #[msg("Challenge window has closed")]
+    ChallengeWindowClosed,
+    #[msg("Challenge window is still open")]
+    ChallengeWindowOpen,
+    #[msg("The committee has not ruled yet")]
+    RulingPending,
+    #[msg("Disputer token account required")]
+    MissingDisputerAccount,
+    #[msg("Committee needs 1-10 distinct members and a majority threshold")]
+    InvalidCommittee,
+    #[msg("Committee can only change before the resolution time")]
+    CommitteeLocked,
+    #[msg("Wrong committee for this market")]
+    CommitteeMismatch,
+    #[msg("No dispute committee has been set up")]
+    NoCommittee,
+    #[msg("Proposal has not been disputed")]
+    ProposalNotDisputed,
+    #[msg("The committee has already ruled")]
+    RulingReached,
+    #[msg("Signer is not a committee member")]
+    NotCommitteeMember,
+    #[msg("Committee member already voted")]
+    AlreadyVoted,
+    #[msg("Market already has trading volume")]
+    MarketHasVolume,
+    #[msg("Market resolves from its price feed")]
+    PriceFeedMarket,
+    #[msg("Invalid price feed account")]
+    InvalidPriceFeed,
+    #[msg("Price feed is not trading")]
+    PriceNotTrading,
+    #[msg("Price is too far from the resolution time")]
+    StalePrice,
+    #[msg("Resolution grace period must be positive")]
+    InvalidResolutionGrace,
+    #[msg("Resolution grace period has not ended")]
+    GracePeriodActive,
+    #[msg("Trading on this market has closed")]
+    TradingClosed,
+    #[msg("Close time must be between now and the resolution time")]
+    InvalidCloseTime,
+    #[msg("Resolution time has passed; resolve normally")]
+    NotEarly,
+    #[msg("Insufficient LP shares")]
+    InsufficientLpShares,
+    #[msg("Not supported by this market's pricing engine")]
+    WrongPricingEngine,
+    #[msg("Invalid LMSR liquidity parameter")]
+    InvalidLiquidityParameter,
+    #[msg("LMSR calculation out of range")]
+    LmsrOutOfRange,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+
+    #[test]
+    fn cpmm_buys_keep_the_vault_solvent() {
+        // Seed 100/100 backed by 100 collateral, then two 100-unit YES buys
+        let (mut yes_pool, mut no_pool, mut vault) = (100u64, 100u64, 100u64);
+        let mut trader_yes = 0;
+        for _ in 0..2 {
+            let (shares, y, n) = cpmm_buy(yes_pool, no_pool, 100, true);
+            (yes_pool, no_pool) = (y, n);
+            trader_yes += shares;
+            vault += 100;
+        }
+
+        assert_eq!(vault, 300);
+        assert_eq!((yes_pool, no_pool), (34, 300));
+        // Every YES share, the pool's included, is backed by collateral
+        assert!(trader_yes + yes_pool <= vault);
+        assert!(no_pool <= vault);
+    }
+
+    #[test]
+    fn cpmm_trades_never_lower_the_invariant() {
+        let (yes_pool, no_pool) = (1_000_003u64, 2_500_017u64);
+        let k = yes_pool as u128 * no_pool as u128;
+        for amount in [1, 7, 999, 123_456, 5_000_000] {
+            for is_yes in [true, false] {
+                let (_, y, n) = cpmm_buy(yes_pool, no_pool, amount, is_yes);
+                assert!(y as u128 * n as u128 >= k);
+
+                let (_, y, n) = cpmm_sell(yes_pool, no_pool, amount, is_yes);
+                assert!(y as u128 * n as u128 >= k);
+            }
+        }
+    }
+
+    #[test]
+    fn cpmm_round_trip_does_not_profit() {
+        for amount in [1, 50, 10_000, 777_777] {
+            let (shares, y, n) = cpmm_buy(1_000_000, 400_000, amount, false);
+            let (payout, _, _) = cpmm_sell(y, n, shares, false);
+            assert!(payout <= amount);
+        }
+    }
+
+    #[test]
+    fn lp_round_trip_keeps_claims_within_the_vault() {
+        // Seed 1_000/1_000; LP shares follow the seed
+        let (mut yes_pool, mut no_pool, mut total_lp, mut vault) = (1_000u64, 1_000u64, 1_000u64, 1_000u64);
+        let (mut trader_yes, mut trader_no, mut lp_yes, mut lp_no) = (0u64, 0u64, 0u64, 0u64);
+        let solvent = |yes_pool: u64, no_pool: u64, yes: u64, no: u64, vault: u64| {
+            yes_pool + yes <= vault && no_pool + no <= vault
+        };
+
+        for (amount, is_yes) in [(300, true), (77, false), (1_234, true)] {
+            let (shares, y, n) = cpmm_buy(yes_pool, no_pool, amount, is_yes);
+            (yes_pool, no_pool) = (y, n);
+            if is_yes { trader_yes += shares } else { trader_no += shares }
+            vault += amount;
+            assert!(solvent(yes_pool, no_pool, trader_yes + lp_yes, trader_no + lp_no, vault));
+        }
+
+        // A second provider joins at the skewed price
+        let deposit = 555;
+        let (yes_added, no_added, minted) = lp_deposit(yes_pool, no_pool, total_lp, deposit);
+        yes_pool += yes_added;
+        no_pool += no_added;
+        total_lp += minted;
+        lp_yes += deposit - yes_added;
+        lp_no += deposit - no_added;
+        vault += deposit;
+        assert!(solvent(yes_pool, no_pool, trader_yes + lp_yes, trader_no + lp_no, vault));
+
+        let (payout, y, n) = cpmm_sell(yes_pool, no_pool, 400, true);
+        (yes_pool, no_pool) = (y, n);
+        trader_yes -= 400;
+        vault -= payout;
+        assert!(solvent(yes_pool, no_pool, trader_yes + lp_yes, trader_no + lp_no, vault));
+
+        // Both providers exit, leaving one LP share to keep the pools priced
+        for lp_shares in [minted, total_lp - minted - 1] {
+            let (yes_out, no_out) = lp_withdrawal(yes_pool, no_pool, total_lp, lp_shares);
+            let collateral_out = yes_out.min(no_out);
+            yes_pool -= yes_out;
+            no_pool -= no_out;
+            total_lp -= lp_shares;
+            lp_yes += yes_out - collateral_out;
+            lp_no += no_out - collateral_out;
+            vault -= collateral_out;
+            assert!(solvent(yes_pool, no_pool, trader_yes + lp_yes, trader_no + lp_no, vault));
+        }
+        assert_eq!(total_lp, 1);
+    }
+
+    #[test]
+    fn max_affordable_fill_matches_brute_force() {
+        for price in [1, 2, 333, 5000, 9998, 9999] {
+            for remaining in [1, 3, 17, 10_000, 12_345] {
+                for budget in [0, 1, 2, 5, 99, 1_000, 50_000] {
+                    for is_complement in [false, true] {
+                        let cost = |fill: u64| {
+                            let maker_cost = fill_notional(price, remaining, fill);
+                            if is_complement { fill - maker_cost } else { maker_cost }
+                        };
+                        let expected = (0..=remaining).rev().find(|&f| cost(f) <= budget).unwrap();
+                        assert_eq!(
+                            max_affordable_fill(price, remaining, budget, is_complement),
+                            expected,
+                            "price {price} remaining {remaining} budget {budget} complement {is_complement}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Zeroed orderbook on the heap; too large for the test thread's stack
+    fn empty_orderbook() -> Vec<u64> {
+        assert_eq!(std::mem::size_of::<Orderbook>() % 8, 0);
+        vec![0u64; std::mem::size_of::<Orderbook>() / 8]
+    }
+
+    fn taker_buy_yes(owner: Pubkey, limit_price: u64, amount: u64) -> TakerOrder {
+        TakerOrder {
+            owner,
+            is_yes: true,
+            is_buy: true,
+            limit_price,
+            amount,
+            amount_is_collateral: false,
+            self_trade: SelfTradeMode::CancelTaker,
+        }
+    }
+
+    #[test]
+    fn match_orders_fills_by_price_then_time() {
+        let mut storage = empty_orderbook();
+        let book: &mut Orderbook = bytemuck::from_bytes_mut(bytemuck::cast_slice_mut(&mut storage));
+        let (early, late, cheap, taker) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        book.rest_order(&early, true, false, 6000, 10_000, 0).unwrap();
+        book.rest_order(&late, true, false, 6000, 10_000, 0).unwrap();
+        book.rest_order(&cheap, true, false, 5500, 10_000, 0).unwrap();
+
+        // The cheaper ask fills first, then the older of the two at 6000
+        let result = match_orders(book, &taker_buy_yes(taker, 6000, 15_000), 0, 1).unwrap();
+        assert_eq!(result.filled_amount, 15_000);
+        assert_eq!(result.notional, 5500 + 3000);
+
+        let asks = book.sides[side_index(true, false)].orders();
+        assert_eq!(asks.len(), 2);
+        assert_eq!((asks[1].owner, asks[1].qty), (early, 5_000));
+        assert_eq!((asks[0].owner, asks[0].qty), (late, 10_000));
+
+        let cheap_slot = book.find_owner(&cheap).unwrap();
+        assert_eq!(book.balances[cheap_slot].open_orders, 0);
+        assert_eq!(book.balances[cheap_slot].yes_sold, 10_000);
+    }
+
+    #[test]
+    fn match_orders_partially_fills_against_a_budget() {
+        let mut storage = empty_orderbook();
+        let book: &mut Orderbook = bytemuck::from_bytes_mut(bytemuck::cast_slice_mut(&mut storage));
+        let (maker, taker) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        book.rest_order(&maker, true, false, 4000, 10_000, 0).unwrap();
+        // An expired order at a better price is evicted instead of filled
+        book.rest_order(&maker, true, false, 3000, 10_000, 50).unwrap();
+
+        let mut order = taker_buy_yes(taker, 5000, 2_000);
+        order.amount_is_collateral = true;
+        let result = match_orders(book, &order, 50, 1).unwrap();
+
+        assert_eq!(result.filled_amount, 5_000);
+        assert_eq!(result.notional, 2_000);
+        assert_eq!(result.remaining_amount, 0);
+        let asks = book.sides[side_index(true, false)].orders();
+        assert_eq!((asks.len(), asks[0].qty, asks[0].price), (1, 5_000, 4000));
+
+        let slot = book.find_owner(&maker).unwrap();
+        assert_eq!(book.balances[slot].yes_unlocked, 10_000);
+        assert_eq!(book.balances[slot].collateral, 2_000);
+    }
+
+    #[test]
+    fn smallest_owner_picks_the_least_resting_collateral() {
+        let mut storage = empty_orderbook();
+        let book: &mut Orderbook = bytemuck::from_bytes_mut(bytemuck::cast_slice_mut(&mut storage));
+        let (whale, dust) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        book.rest_order(&whale, true, true, 5000, 1_000, 0).unwrap();
+        book.rest_order(&dust, true, true, 100, 100, 0).unwrap();
+        let whale_slot = book.find_owner(&whale).unwrap();
+        assert_eq!(book.smallest_owner(), book.find_owner(&dust));
+        assert_eq!(book.balances[whale_slot].resting, 500);
+
+        // Fills and amendments keep each owner's resting value current
+        let order = TakerOrder { is_buy: false, ..taker_buy_yes(Pubkey::new_unique(), 5000, 400) };
+        match_orders(book, &order, 0, 1).unwrap();
+        assert_eq!(book.balances[whale_slot].resting, 300);
+        let whale_order = book.sides[side_index(true, true)].orders()[1].seq;
+        book.amend_order(&whale, true, true, whale_order, 5000, 100).unwrap();
+        assert_eq!(book.balances[whale_slot].resting, 50);
+
+        // Cancelling stops once the owner's orders are gone and keeps the rest in order
+        book.cancel_all(&dust);
+        let owners: Vec<_> = book.sides[side_index(true, true)].orders().iter().map(|o| o.owner).collect();
+        assert_eq!(owners, vec![whale]);
+        let slot = book.find_owner(&dust).unwrap();
+        assert_eq!((book.balances[slot].open_orders, book.balances[slot].resting), (0, 0));
+        assert!(book.balances[slot].has_unsettled());
+    }
+
+    #[test]
+    fn cost_basis_counts_filled_bids_and_stays_signed() {
+        let mut storage = empty_orderbook();
+        let book: &mut Orderbook = bytemuck::from_bytes_mut(bytemuck::cast_slice_mut(&mut storage));
+        let (bidder, taker) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // A 10_000-share bid at 4000 fills 2_500 shares and is then cancelled
+        book.rest_order(&bidder, true, true, 4000, 10_000, 0).unwrap();
+        let order = TakerOrder { is_buy: false, ..taker_buy_yes(taker, 4000, 2_500) };
+        match_orders(book, &order, 0, 1).unwrap();
+        book.cancel_all(&bidder);
+
+        let mut position = UserPosition {
+            owner: bidder,
+            market: Pubkey::default(),
+            yes_shares: 0,
+            no_shares: 0,
+            locked_yes_shares: 0,
+            locked_no_shares: 0,
+            cost_basis: 0,
+        };
+        let mut refund_basis = 0;
+        let payout = settle_owner_balance(book, &bidder, Some(&mut position), None, &mut refund_basis).unwrap();
+        // Only the filled part of the escrow is paid into the market
+        assert_eq!((payout, position.cost_basis, position.yes_shares), (3_000, 1_000, 2_500));
+        assert_eq!(refund_basis, 1_000);
+        assert!(book.find_owner(&bidder).is_none());
+
+        // Taking out more than was paid in leaves a negative basis, which
+        // drops out of the refund total instead of offsetting other positions
+        position.reduce_cost_basis(1_500, &mut refund_basis);
+        assert_eq!((position.cost_basis, refund_basis), (-500, 0));
+        position.add_cost_basis(800, &mut refund_basis);
+        assert_eq!((position.cost_basis, refund_basis), (300, 300));
+        assert_eq!(position.take_refund_weight(&mut refund_basis), 300);
+        assert_eq!((position.cost_basis, refund_basis), (0, 0));
+    }
+
+    fn user_position(owner: Pubkey) -> UserPosition {
+        UserPosition {
+            owner,
+            market: Pubkey::default(),
+            yes_shares: 0,
+            no_shares: 0,
+            locked_yes_shares: 0,
+            locked_no_shares: 0,
+            cost_basis: 0,
+        }
+    }
+
+    #[test]
+    fn na_refunds_stay_within_the_vault_with_a_net_negative_trader() {
+        let mut refund_basis = 0;
+        let mut vault = 0;
+        let [mut creator, mut alice, mut bob] = [0; 3].map(|_| user_position(Pubkey::new_unique()));
+
+        // Seed 100; Alice buys 50; Bob buys 100; Alice sells for 70
+        creator.add_cost_basis(100, &mut refund_basis);
+        alice.add_cost_basis(50, &mut refund_basis);
+        bob.add_cost_basis(100, &mut refund_basis);
+        alice.reduce_cost_basis(70, &mut refund_basis);
+        vault += 100 + 50 + 100;
+        vault -= 70;
+        assert_eq!((alice.cost_basis, refund_basis, vault), (-20, 200, 180));
+
+        // Clamped bases would owe 200; shared pro rata they owe what the vault holds
+        let mut paid = 0;
+        for position in [&mut creator, &mut alice, &mut bob] {
+            let weight = position.cost_basis.max(0) as u64;
+            let refund = refund_share(weight, vault, refund_basis);
+            position.take_refund_weight(&mut refund_basis);
+            vault -= refund;
+            paid += refund;
+        }
+        assert_eq!((paid, vault, refund_basis), (180, 0, 0));
+    }
+
+    /// Pyth v2 price account with the aggregate fields filled in
+    fn price_account(price: i64, expo: i32, publish_time: i64, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; 240];
+        data[0..4].copy_from_slice(&PRICE_FEED_MAGIC.to_le_bytes());
+        data[8..12].copy_from_slice(&PRICE_ACCOUNT_TYPE.to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&25u64.to_le_bytes());
+        data[224..228].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn read_price_feed_parses_the_aggregate_price() {
+        let reading = read_price_feed(&price_account(6_512_345_000_000, -8, 1_700_000_000, PRICE_STATUS_TRADING)).unwrap();
+        assert_eq!(reading.price, 6_512_345_000_000);
+        assert_eq!(reading.expo, -8);
+        assert_eq!(reading.publish_time, 1_700_000_000);
+        assert_eq!(reading.conf, 25);
+        // 65_123.45 is above a 65_000 strike, below 65_200
+        assert!(compare_prices(reading.price, reading.expo, 65_000, 0).is_gt());
+        assert!(compare_prices(reading.price, reading.expo, 652, 2).is_lt());
+
+        assert!(read_price_feed(&price_account(1, 0, 0, 0)).is_err());
+        let mut wrong_magic = price_account(1, 0, 0, PRICE_STATUS_TRADING);
+        wrong_magic[0] ^= 1;
+        assert!(read_price_feed(&wrong_magic).is_err());
+        assert!(read_price_feed(&price_account(1, 0, 0, PRICE_STATUS_TRADING)[..200]).is_err());
+    }
+
+    fn lmsr_market(answer_count: u8, b: u64) -> MultiMarket {
+        MultiMarket {
+            pricing: PricingEngine::Lmsr,
+            lmsr_b: b,
+            tick_size: 1,
+            min_order_qty: 1,
+            ..one_winner_market(Pubkey::default(), answer_count)
+        }
+    }
+
+    #[test]
+    fn exp_wad_matches_f64_across_its_range() {
+        assert_eq!(exp_wad(0).unwrap(), WAD);
+        assert_eq!(exp_wad(-43 * WAD).unwrap(), 1);
+        assert!(exp_wad(40 * WAD + 1).is_err());
+
+        for x in [-42 * WAD, -41 * WAD - WAD / 3, -20 * WAD, -WAD, -1, 1, 1_000_000_000, WAD / 2, WAD, 10 * WAD, 39 * WAD + 7, 40 * WAD] {
+            let expected = (x as f64 / WAD as f64).exp() * WAD as f64;
+            let got = exp_wad(x).unwrap() as f64;
+            assert!((got - expected).abs() <= expected * 1e-12 + 2.0, "exp({x}): {got} vs {expected}");
+        }
+    }
+
+    #[test]
+    fn ln_wad_matches_f64_across_its_range() {
+        assert_eq!(ln_wad(WAD).unwrap(), 0);
+        assert!(ln_wad(0).is_err());
+        assert!(ln_wad(-WAD).is_err());
+
+        for x in [1, 1_000, WAD / 3, WAD - 1, WAD + 1, 2 * WAD, 2_718_281_828_459_045_235, 1_000_000 * WAD, exp_wad(40 * WAD).unwrap()] {
+            let expected = (x as f64 / WAD as f64).ln() * WAD as f64;
+            let got = ln_wad(x).unwrap() as f64;
+            assert!((got - expected).abs() <= expected.abs() * 1e-12 + 1e4, "ln({x}): {got} vs {expected}");
+        }
+        // ln undoes exp to within rounding; very negative x loses digits to the WAD scale
+        for x in [-5 * WAD, -WAD / 7, WAD / 7, 30 * WAD] {
+            assert!((ln_wad(exp_wad(x).unwrap()).unwrap() - x).abs() < 1_000_000);
+        }
+    }
+
+    #[test]
+    fn lmsr_buys_get_dearer_as_an_answer_is_bought() {
+        let mut market = lmsr_market(4, 10_000);
+        let mut last = u64::MAX;
+        for _ in 0..10 {
+            let shares = lmsr_shares_out(&market, 1, 1_000, true).unwrap();
+            assert!(shares < last);
+            last = shares;
+            lmsr_apply(&mut market, 1, shares, true, true);
+        }
+
+        // Spending more never buys fewer shares, on either side, up to the b trade cap
+        for is_yes in [true, false] {
+            let mut last = 0;
+            for amount in [1, 10, 100, 1_000, 5_000, 10_000] {
+                let shares = lmsr_shares_out(&market, 2, amount, is_yes).unwrap();
+                assert!(shares >= last);
+                last = shares;
+            }
+        }
+    }
+
+    #[test]
+    fn lmsr_round_trip_does_not_profit() {
+        let mut market = lmsr_market(3, 5_000);
+        lmsr_apply(&mut market, 0, 2_000, true, true);
+        for is_yes in [true, false] {
+            for amount in [1, 99, 2_500, 5_000] {
+                let shares = lmsr_shares_out(&market, 1, amount, is_yes).unwrap();
+                let mut after = market.clone();
+                lmsr_apply(&mut after, 1, shares, is_yes, true);
+                assert!(lmsr_payout(&after, 1, shares, is_yes).unwrap() <= amount);
+            }
+        }
+    }
+
+    #[test]
+    fn lmsr_subsidy_covers_the_worst_case_payout() {
+        for (answer_count, b) in [(2u8, 100u64), (3, 7_777), (10, 1_000_000)] {
+            let mut market = lmsr_market(answer_count, b);
+            let ln_n = ln_wad(answer_count as i128 * WAD).unwrap();
+            let subsidy = (b as i128 * ln_n / WAD) as u64 + 1;
+            let mut collected: u64 = 0;
+
+            for step in 0..40u64 {
+                let index = (step * 7 % answer_count as u64) as u8;
+                let is_yes = step % 3 != 0;
+                // Buys are capped at b per trade
+                let amount = 1 + step * step * b / 1_600;
+                let shares = lmsr_shares_out(&market, index, amount, is_yes).unwrap();
+                lmsr_apply(&mut market, index, shares, is_yes, true);
+                collected += amount;
+
+                // Whichever answer wins, its net YES shares are what the market owes
+                let worst = market.lmsr_q[..answer_count as usize].iter().copied().max().unwrap();
+                assert!(worst <= (subsidy + collected) as i64, "N={answer_count} b={b} step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn ceil_sqrt_rounds_up() {
+        for x in [0u128, 1, 2, 3, 4, 15, 16, 17, 1 << 100, u64::MAX as u128 * u64::MAX as u128] {
+            let r = ceil_sqrt(x);
+            assert!(r * r >= x);
+            assert!(r == 0 || (r - 1) * (r - 1) < x);
+        }
+    }
+
+    // Instruction handlers run here against leaked accounts, with a stubbed clock
+
+    thread_local! {
+        static NOW: std::cell::Cell<i64> = const { std::cell::Cell::new(0) };
+    }
+
+    struct TestRuntime;
+
+    impl anchor_lang::solana_program::program_stubs::SyscallStubs for TestRuntime {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let clock = Clock { unix_timestamp: NOW.with(|now| now.get()), ..Clock::default() };
+            unsafe { *(var_addr as *mut Clock) = clock };
+            anchor_lang::solana_program::entrypoint::SUCCESS
+        }
+
+        /// Token transfers move the balance between the accounts passed in
+        fn sol_invoke_signed(
+            &self,
+            instruction: &anchor_lang::solana_program::instruction::Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> std::result::Result<(), ProgramError> {
+            use anchor_spl::token::spl_token::instruction::TokenInstruction;
+            assert_eq!(instruction.program_id, token::ID);
+            let TokenInstruction::Transfer { amount } = TokenInstruction::unpack(&instruction.data)? else {
+                panic!("unsupported token instruction");
+            };
+            let info = |at: usize| account_infos.iter().find(|i| *i.key == instruction.accounts[at].pubkey).unwrap();
+            let from = token_balance(info(0)).checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+            set_token_balance(info(0), from);
+            set_token_balance(info(1), token_balance(info(1)) + amount);
+            Ok(())
+        }
+    }
+
+    fn set_now(unix_timestamp: i64) {
+        static STUBS: std::sync::Once = std::sync::Once::new();
+        STUBS.call_once(|| {
+            anchor_lang::solana_program::program_stubs::set_syscall_stubs(Box::new(TestRuntime));
+        });
+        NOW.with(|now| now.set(unix_timestamp));
+    }
+
+    fn leak_info(key: Pubkey, owner: Pubkey, data: Vec<u8>, is_signer: bool) -> &'static AccountInfo<'static> {
+        Box::leak(Box::new(AccountInfo::new(
+            Box::leak(Box::new(key)),
+            is_signer,
+            true,
+            Box::leak(Box::new(0)),
+            Box::leak(data.into_boxed_slice()),
+            Box::leak(Box::new(owner)),
+            false,
+            0,
+        )))
+    }
+
+    /// Program-owned account holding `value`, sized for its largest encoding
+    fn program_info<T: AccountSerialize + Space>(value: &T) -> &'static AccountInfo<'static> {
+        let mut data = Vec::with_capacity(8 + T::INIT_SPACE);
+        value.try_serialize(&mut data).unwrap();
+        data.resize(8 + T::INIT_SPACE, 0);
+        leak_info(Pubkey::new_unique(), crate::ID, data, false)
+    }
+
+    fn signer_info() -> &'static AccountInfo<'static> {
+        leak_info(Pubkey::new_unique(), System::id(), Vec::new(), true)
+    }
+
+    fn program_id_info(id: Pubkey) -> &'static AccountInfo<'static> {
+        let info = leak_info(id, Pubkey::default(), Vec::new(), false);
+        Box::leak(Box::new(AccountInfo { executable: true, ..info.clone() }))
+    }
+
+    fn token_info(mint: Pubkey, owner: Pubkey, amount: u64) -> &'static AccountInfo<'static> {
+        use anchor_spl::token::spl_token::state::{Account as SplAccount, AccountState};
+        let mut data = vec![0; SplAccount::LEN];
+        SplAccount { mint, owner, amount, state: AccountState::Initialized, ..Default::default() }
+            .pack_into_slice(&mut data);
+        leak_info(Pubkey::new_unique(), token::ID, data, false)
+    }
+
+    fn token_account(info: &AccountInfo) -> anchor_spl::token::spl_token::state::Account {
+        anchor_spl::token::spl_token::state::Account::unpack(&info.try_borrow_data().unwrap()).unwrap()
+    }
+
+    fn token_balance(info: &AccountInfo) -> u64 {
+        token_account(info).amount
+    }
+
+    fn set_token_balance(info: &AccountInfo, amount: u64) {
+        use anchor_spl::token::spl_token::state::Account as SplAccount;
+        let mut data = info.try_borrow_mut_data().unwrap();
+        let account = SplAccount { amount, ..SplAccount::unpack(&data).unwrap() };
+        account.pack_into_slice(&mut data);
+    }
+
+    fn load<T: AccountDeserialize>(info: &AccountInfo) -> T {
+        T::try_deserialize(&mut &info.try_borrow_data().unwrap()[..]).unwrap()
+    }
+
+    /// An all-default account of type `T`, for tests to fill in
+    fn zeroed<T: AnchorDeserialize + Space>() -> T {
+        T::deserialize(&mut &vec![0u8; T::INIT_SPACE][..]).unwrap()
+    }
+
+    fn one_winner_market(resolver: Pubkey, answer_count: u8) -> MultiMarket {
+        MultiMarket { creator: resolver, resolver, answer_count, is_one_winner: true, ..zeroed() }
+    }
+
+    fn answer_infos(market: Pubkey, answer_count: u8) -> Vec<&'static AccountInfo<'static>> {
+        (0..answer_count)
+            .map(|index| program_info(&Answer { market, index, ..zeroed() }))
+            .collect()
+    }
+
+    fn resolve<'info>(
+        market: &'info AccountInfo<'info>,
+        answer: &'info AccountInfo<'info>,
+        resolver: &'info AccountInfo<'info>,
+        siblings: &'info [AccountInfo<'info>],
+        outcome: MarketOutcome,
+    ) -> Result<MultiMarket> {
+        let mut accounts = ResolveAnswer {
+            market: Account::try_from(market)?,
+            answer: Account::try_from(answer)?,
+            resolver: Signer::try_from(resolver)?,
+        };
+        likeli_contracts::resolve_answer(
+            Context::new(&crate::ID, &mut accounts, siblings, Default::default()),
+            outcome,
+        )?;
+        accounts.answer.exit(&crate::ID)?;
+        Ok(accounts.market.into_inner())
+    }
+
+    fn leak_slice(infos: &[&'static AccountInfo<'static>]) -> &'static [AccountInfo<'static>] {
+        Box::leak(infos.iter().map(|info| (*info).clone()).collect::<Vec<_>>().into_boxed_slice())
+    }
+
+    #[test]
+    fn one_winner_yes_resolves_the_other_answers_no() {
+        set_now(0);
+        let resolver = signer_info();
+        let market = program_info(&one_winner_market(resolver.key(), 3));
+        let answers = answer_infos(market.key(), 3);
+
+        // Resolving NO leaves the rest open
+        let state = resolve(market, answers[0], resolver, &[], MarketOutcome::No).unwrap();
+        assert_eq!((state.answers_resolved, state.resolved_mask, state.yes_mask), (1, 0b001, 0));
+        assert!(!state.resolved);
+
+        state.try_serialize(&mut &mut market.try_borrow_mut_data().unwrap()[..]).unwrap();
+        // The winner must bring every unresolved sibling along
+        let err = resolve(market, answers[1], resolver, leak_slice(&answers[..1]), MarketOutcome::Yes).err();
+        assert_eq!(err, Some(LikeliError::MissingSiblings.into()));
+
+        let state = resolve(market, answers[1], resolver, leak_slice(&answers), MarketOutcome::Yes).unwrap();
+        assert_eq!(state.winning_answer, Some(1));
+        assert_eq!((state.answers_resolved, state.resolved_mask, state.yes_mask), (3, 0b111, 0b010));
+        assert!(state.resolved);
+        for (index, info) in answers.iter().enumerate() {
+            let answer: Answer = load(info);
+            let expected = if index == 1 { MarketOutcome::Yes } else { MarketOutcome::No };
+            assert_eq!(answer.outcome, Some(expected));
+            assert_eq!(state.answer_outcome(index as u8), Some(expected));
+        }
+    }
+
+    #[test]
+    fn one_winner_mode_locks_once_the_market_trades_or_resolves() {
+        set_now(0);
+        let creator = signer_info();
+        let set_one_winner = |market: MultiMarket, is_one_winner: bool| -> Result<MultiMarket> {
+            let mut accounts = SetMultiMarketConfig {
+                market: Account::try_from(program_info(&market))?,
+                creator: Signer::try_from(creator)?,
+            };
+            likeli_contracts::set_multi_market_config(
+                Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+                is_one_winner,
+                0,
+                0,
+            )?;
+            Ok(accounts.market.into_inner())
+        };
+
+        let fresh = one_winner_market(creator.key(), 3);
+        assert!(!set_one_winner(fresh.clone(), false).unwrap().is_one_winner);
+        for locked in [
+            MultiMarket { volume: 1, ..fresh.clone() },
+            MultiMarket { answers_resolved: 1, ..fresh.clone() },
+        ] {
+            assert_eq!(set_one_winner(locked.clone(), false).err(), Some(LikeliError::OneWinnerLocked.into()));
+            // Other settings stay editable
+            assert!(set_one_winner(locked, true).unwrap().is_one_winner);
+        }
+    }
+
+    fn binary_market(resolver: Pubkey, resolution_time: i64) -> Market {
+        Market { creator: resolver, resolver, resolution_time, close_time: resolution_time, ..zeroed() }
+    }
+
+    fn resolve_binary(
+        handler: fn(Context<ResolveMarket>, MarketOutcome) -> Result<()>,
+        market: Market,
+        resolver: &'static AccountInfo<'static>,
+        outcome: MarketOutcome,
+    ) -> Result<Market> {
+        let mut accounts = ResolveMarket {
+            market: Account::try_from(program_info(&market))?,
+            resolver: Signer::try_from(resolver)?,
+        };
+        handler(Context::new(&crate::ID, &mut accounts, &[], Default::default()), outcome)?;
+        Ok(accounts.market.into_inner())
+    }
+
+    #[test]
+    fn probability_payouts_never_exceed_a_complete_set() {
+        for bps in [0, 1, 3333, 5000, 9999, 10000] {
+            for shares in [0, 1, 3, 7, 1_000_001, u64::MAX / 2] {
+                assert_eq!(probability_payout(shares, shares, bps), shares);
+                // Split across two holders the halves round down
+                let split = probability_payout(shares, 0, bps) + probability_payout(0, shares, bps);
+                assert!(split <= shares && split + 1 >= shares, "{shares} @ {bps}");
+            }
+        }
+        assert_eq!(probability_payout(100, 40, 2500), 25 + 30);
+    }
+
+    #[test]
+    fn probability_resolution_pays_by_the_resolved_bps() {
+        set_now(100);
+        let resolver = signer_info();
+        let market = binary_market(resolver.key(), 100);
+
+        let err = resolve_binary(likeli_contracts::resolve_market, market.clone(), resolver, MarketOutcome::Probability(10001)).err();
+        assert_eq!(err, Some(LikeliError::InvalidProbability.into()));
+
+        let resolved = resolve_binary(likeli_contracts::resolve_market, market, resolver, MarketOutcome::Probability(3000)).unwrap();
+        assert!(resolved.resolved);
+        assert_eq!(resolved.resolution_bps, 3000);
+        let position = UserPosition { yes_shares: 100, no_shares: 50, ..user_position(Pubkey::default()) };
+        assert_eq!(position.payout(resolved.outcome.unwrap()), 30 + 35);
+    }
+
+    #[test]
+    fn early_resolution_is_only_for_the_resolver_before_the_resolution_time() {
+        let resolver = signer_info();
+        let market = binary_market(resolver.key(), 1_000);
+
+        set_now(400);
+        let err = resolve_binary(likeli_contracts::resolve_market, market.clone(), resolver, MarketOutcome::Yes).err();
+        assert_eq!(err, Some(LikeliError::TooEarlyToResolve.into()));
+        let err = resolve_binary(likeli_contracts::resolve_market_early, market.clone(), signer_info(), MarketOutcome::Yes).err();
+        assert_eq!(err, Some(LikeliError::Unauthorized.into()));
+
+        // Resolving early halts trading from now on
+        let resolved = resolve_binary(likeli_contracts::resolve_market_early, market.clone(), resolver, MarketOutcome::Yes).unwrap();
+        assert_eq!((resolved.outcome, resolved.close_time), (Some(MarketOutcome::Yes), 400));
+
+        set_now(1_000);
+        let err = resolve_binary(likeli_contracts::resolve_market_early, market, resolver, MarketOutcome::Yes).err();
+        assert_eq!(err, Some(LikeliError::NotEarly.into()));
+    }
+
+    #[test]
+    fn close_time_stays_between_now_and_the_resolution_time() {
+        let creator = signer_info();
+        let set_close_time = |close_time: i64| -> Result<i64> {
+            let mut accounts = SetMarketFees {
+                market: Account::try_from(program_info(&binary_market(creator.key(), 1_000)))?,
+                creator: Signer::try_from(creator)?,
+            };
+            likeli_contracts::set_close_time(Context::new(&crate::ID, &mut accounts, &[], Default::default()), close_time)?;
+            Ok(accounts.market.close_time)
+        };
+
+        set_now(500);
+        assert_eq!(set_close_time(500).unwrap(), 500);
+        assert_eq!(set_close_time(1_000).unwrap(), 1_000);
+        assert_eq!(set_close_time(499).err(), Some(LikeliError::InvalidCloseTime.into()));
+        assert_eq!(set_close_time(1_001).err(), Some(LikeliError::InvalidCloseTime.into()));
+    }
+
+    const BOND: u64 = 50;
+
+    /// A binary market with its oracle configured: the resolver and two
+    /// traders each hold 1000 collateral to post bonds with
+    struct Oracle {
+        market: Market,
+        resolver: &'static AccountInfo<'static>,
+        traders: [&'static AccountInfo<'static>; 2],
+        atas: [&'static AccountInfo<'static>; 3],
+        committee: [&'static AccountInfo<'static>; 3],
+        committee_info: &'static AccountInfo<'static>,
+        mint: &'static AccountInfo<'static>,
+        bond_vault: &'static AccountInfo<'static>,
+        vault_ata: &'static AccountInfo<'static>,
+    }
+
+    impl Oracle {
+        fn new() -> Self {
+            use anchor_spl::token::spl_token::state::Mint as SplMint;
+            let mut mint_data = vec![0; SplMint::LEN];
+            SplMint { is_initialized: true, ..Default::default() }.pack_into_slice(&mut mint_data);
+            let mint = leak_info(Pubkey::new_unique(), token::ID, mint_data, false);
+
+            let resolver = signer_info();
+            let traders = [signer_info(), signer_info()];
+            let atas = [resolver, traders[0], traders[1]].map(|owner| token_info(mint.key(), owner.key(), 1000));
+            let committee = [signer_info(), signer_info(), signer_info()];
+            let committee_info = program_info(&Committee {
+                members: committee.iter().map(|member| member.key()).collect(),
+                threshold: 2,
+                bump: 0,
+            });
+            let market = Market {
+                oracle_bond: BOND,
+                challenge_window: 100,
+                committee: committee_info.key(),
+                ..binary_market(resolver.key(), 1_000)
+            };
+            Oracle {
+                market,
+                resolver,
+                traders,
+                atas,
+                committee,
+                committee_info,
+                mint,
+                bond_vault: token_info(mint.key(), Pubkey::default(), 0),
+                vault_ata: token_info(mint.key(), Pubkey::default(), 0),
+            }
+        }
+
+        /// Token account of the resolver or a trader
+        fn ata_of(&self, owner: Pubkey) -> &'static AccountInfo<'static> {
+            self.atas.iter().copied().find(|ata| token_account(ata).owner == owner).unwrap()
+        }
+
+        fn propose(&mut self, proposer: &'static AccountInfo<'static>, outcome: MarketOutcome) -> Result<Proposal> {
+            let market = program_info(&self.market);
+            let mut accounts = ProposeOutcome {
+                market: Account::try_from(market)?,
+                proposal: Account::try_from(program_info(&zeroed::<Proposal>()))?,
+                vault_authority: UncheckedAccount::try_from(signer_info()),
+                vault_ata: Account::try_from(self.vault_ata)?,
+                bond_vault: Account::try_from(self.bond_vault)?,
+                collateral_mint: Account::try_from(self.mint)?,
+                proposer_ata: Account::try_from(self.ata_of(proposer.key()))?,
+                proposer: Signer::try_from(proposer)?,
+                system_program: Program::try_from(program_id_info(System::id()))?,
+                token_program: Program::try_from(program_id_info(token::ID))?,
+            };
+            likeli_contracts::propose_outcome(Context::new(&crate::ID, &mut accounts, &[], Default::default()), outcome)?;
+            self.market = accounts.market.into_inner();
+            Ok(accounts.proposal.into_inner())
+        }
+
+        fn dispute(&self, proposal: &Proposal, disputer: &'static AccountInfo<'static>) -> Result<Proposal> {
+            let mut accounts = DisputeProposal {
+                proposal: Account::try_from(program_info(proposal))?,
+                market: UncheckedAccount::try_from(program_info(&self.market)),
+                committee: UncheckedAccount::try_from(self.committee_info),
+                bond_vault: Account::try_from(self.bond_vault)?,
+                disputer_ata: Account::try_from(self.ata_of(disputer.key()))?,
+                disputer: Signer::try_from(disputer)?,
+                token_program: Program::try_from(program_id_info(token::ID))?,
+            };
+            likeli_contracts::dispute_proposal(Context::new(&crate::ID, &mut accounts, &[], Default::default()))?;
+            Ok(accounts.proposal.into_inner())
+        }
+
+        fn vote(&self, proposal: &Proposal, member: &'static AccountInfo<'static>, outcome: MarketOutcome) -> Result<Proposal> {
+            let mut accounts = VoteOnDispute {
+                proposal: Account::try_from(program_info(proposal))?,
+                member: Signer::try_from(member)?,
+            };
+            likeli_contracts::vote_on_dispute(Context::new(&crate::ID, &mut accounts, &[], Default::default()), outcome)?;
+            Ok(accounts.proposal.into_inner())
+        }
+
+        fn finalize(&mut self, proposal: &Proposal) -> Result<()> {
+            let disputer_ata = match proposal.disputer {
+                Some(disputer) => Some(Account::try_from(self.ata_of(disputer))?),
+                None => None,
+            };
+            let mut accounts = FinalizeProposal {
+                market: Account::try_from(program_info(&self.market))?,
+                proposal: Account::try_from(program_info(proposal))?,
+                vault_authority: UncheckedAccount::try_from(signer_info()),
+                bond_vault: Account::try_from(self.bond_vault)?,
+                proposer_ata: Account::try_from(self.ata_of(proposal.proposer))?,
+                disputer_ata,
+                proposer: UncheckedAccount::try_from(signer_info()),
+                token_program: Program::try_from(program_id_info(token::ID))?,
+            };
+            likeli_contracts::finalize_proposal(Context::new(&crate::ID, &mut accounts, &[], Default::default()))?;
+            self.market = accounts.market.into_inner();
+            Ok(())
+        }
+
+        /// Collateral held by the resolver, both traders and the bond vault
+        fn balances(&self) -> [u64; 4] {
+            [self.atas[0], self.atas[1], self.atas[2], self.bond_vault].map(token_balance)
+        }
+    }
+
+    #[test]
+    fn proposals_need_a_bond_and_open_to_the_resolver_first() {
+        let mut oracle = Oracle::new();
+        let (resolver, stranger) = (oracle.resolver, oracle.traders[0]);
+
+        set_now(1_000);
+        oracle.market.oracle_bond = 0;
+        assert_eq!(oracle.propose(resolver, MarketOutcome::Yes).err(), Some(LikeliError::OracleBondRequired.into()));
+        oracle.market.oracle_bond = BOND;
+
+        set_now(999);
+        assert_eq!(oracle.propose(resolver, MarketOutcome::Yes).err(), Some(LikeliError::TooEarlyToResolve.into()));
+        set_now(1_000 + RESOLVER_EXCLUSIVE_WINDOW - 1);
+        assert_eq!(oracle.propose(stranger, MarketOutcome::Yes).err(), Some(LikeliError::ResolverWindowOpen.into()));
+        assert_eq!(oracle.balances(), [1000, 1000, 1000, 0]);
+
+        let proposal = oracle.propose(resolver, MarketOutcome::Yes).unwrap();
+        assert_eq!((proposal.proposer, proposal.bond), (resolver.key(), BOND));
+        assert_eq!(proposal.challenge_ends, 1_000 + RESOLVER_EXCLUSIVE_WINDOW - 1 + 100);
+        assert!(oracle.market.proposal_pending);
+        assert_eq!(oracle.balances(), [950, 1000, 1000, 50]);
+
+        // Past the exclusive window anyone may propose
+        set_now(1_000 + RESOLVER_EXCLUSIVE_WINDOW);
+        oracle.market.proposal_pending = false;
+        let proposal = oracle.propose(stranger, MarketOutcome::No).unwrap();
+        assert_eq!(proposal.proposer, stranger.key());
+        assert_eq!(oracle.balances(), [950, 950, 1000, 100]);
+    }
+
+    #[test]
+    fn undisputed_proposals_finalize_once_the_challenge_window_ends() {
+        let mut oracle = Oracle::new();
+        set_now(1_000);
+        let proposal = oracle.propose(oracle.resolver, MarketOutcome::No).unwrap();
+
+        set_now(1_099);
+        assert_eq!(oracle.finalize(&proposal).err(), Some(LikeliError::ChallengeWindowOpen.into()));
+
+        set_now(1_100);
+        let err = oracle.dispute(&proposal, oracle.traders[0]).err();
+        assert_eq!(err, Some(LikeliError::ChallengeWindowClosed.into()));
+        oracle.finalize(&proposal).unwrap();
+        assert_eq!(oracle.market.outcome, Some(MarketOutcome::No));
+        assert!(oracle.market.resolved && !oracle.market.proposal_pending);
+        assert_eq!(oracle.balances(), [1000, 1000, 1000, 0]);
+    }
+
+    #[test]
+    fn disputes_pay_both_bonds_to_the_side_the_committee_rules_for() {
+        for (ruling, balances) in [
+            (MarketOutcome::Yes, [1050, 950, 1000, 0]),
+            (MarketOutcome::No, [950, 1050, 1000, 0]),
+        ] {
+            let mut oracle = Oracle::new();
+            set_now(1_000);
+            let proposal = oracle.propose(oracle.resolver, MarketOutcome::Yes).unwrap();
+
+            set_now(1_050);
+            let proposal = oracle.dispute(&proposal, oracle.traders[0]).unwrap();
+            assert_eq!(proposal.disputer, Some(oracle.traders[0].key()));
+            assert_eq!(oracle.balances(), [950, 950, 1000, 100]);
+            let err = oracle.dispute(&proposal, oracle.traders[1]).err();
+            assert_eq!(err, Some(LikeliError::ProposalDisputed.into()));
+
+            // Disputes wait for the committee, however long the challenge window has passed
+            set_now(1_200);
+            assert_eq!(oracle.finalize(&proposal).err(), Some(LikeliError::RulingPending.into()));
+
+            let err = oracle.vote(&proposal, oracle.traders[1], ruling).err();
+            assert_eq!(err, Some(LikeliError::NotCommitteeMember.into()));
+            let proposal = oracle.vote(&proposal, oracle.committee[0], ruling).unwrap();
+            let err = oracle.vote(&proposal, oracle.committee[0], ruling).err();
+            assert_eq!(err, Some(LikeliError::AlreadyVoted.into()));
+            let proposal = oracle.vote(&proposal, oracle.committee[1], MarketOutcome::Invalid).unwrap();
+            assert_eq!(proposal.ruling, None);
+            let proposal = oracle.vote(&proposal, oracle.committee[2], ruling).unwrap();
+            assert_eq!(proposal.ruling, Some(ruling));
+
+            oracle.finalize(&proposal).unwrap();
+            assert_eq!(oracle.market.outcome, Some(ruling));
+            assert_eq!(oracle.balances(), balances);
+        }
+    }
+
+    #[test]
+    fn disputes_without_a_ruling_lapse_to_na_and_refund_both_bonds() {
+        let mut oracle = Oracle::new();
+        set_now(1_000);
+        let proposal = oracle.propose(oracle.resolver, MarketOutcome::Yes).unwrap();
+        let proposal = oracle.dispute(&proposal, oracle.traders[0]).unwrap();
+        assert_eq!(proposal.voting_ends, 1_000 + DISPUTE_VOTING_PERIOD);
+        let proposal = oracle.vote(&proposal, oracle.committee[0], MarketOutcome::No).unwrap();
+
+        set_now(1_000 + DISPUTE_VOTING_PERIOD - 1);
+        assert_eq!(oracle.finalize(&proposal).err(), Some(LikeliError::RulingPending.into()));
+
+        set_now(1_000 + DISPUTE_VOTING_PERIOD);
+        let err = oracle.vote(&proposal, oracle.committee[1], MarketOutcome::No).err();
+        assert_eq!(err, Some(LikeliError::VotingClosed.into()));
+        oracle.finalize(&proposal).unwrap();
+        assert_eq!(oracle.market.outcome, Some(MarketOutcome::Invalid));
+        assert_eq!(oracle.balances(), [1000, 1000, 1000, 0]);
+    }
+
+    #[test]
+    fn disputes_are_judged_by_the_committee_as_it_stood_when_disputed() {
+        let mut oracle = Oracle::new();
+        set_now(1_000);
+        let proposal = oracle.propose(oracle.resolver, MarketOutcome::Yes).unwrap();
+        let proposal = oracle.dispute(&proposal, oracle.traders[0]).unwrap();
+
+        // Swapping the committee out afterwards changes nothing for this dispute
+        let replacement = Committee { members: vec![oracle.resolver.key()], threshold: 1, bump: 0 };
+        replacement.try_serialize(&mut &mut oracle.committee_info.try_borrow_mut_data().unwrap()[..]).unwrap();
+        let err = oracle.vote(&proposal, oracle.resolver, MarketOutcome::Yes).err();
+        assert_eq!(err, Some(LikeliError::NotCommitteeMember.into()));
+        let proposal = oracle.vote(&proposal, oracle.committee[0], MarketOutcome::No).unwrap();
+        assert_eq!(proposal.ruling, None);
+        let proposal = oracle.vote(&proposal, oracle.committee[1], MarketOutcome::No).unwrap();
+        assert_eq!(proposal.ruling, Some(MarketOutcome::No));
+    }
+
+    #[test]
+    fn disputes_need_a_committee_to_escalate_to() {
+        let mut oracle = Oracle::new();
+        set_now(1_000);
+        let proposal = oracle.propose(oracle.resolver, MarketOutcome::Yes).unwrap();
+
+        // Neither a market committee nor a global one: the resolver can't judge its own proposal
+        oracle.market.committee = Pubkey::default();
+        let global = Pubkey::find_program_address(&[COMMITTEE_SEED], &crate::ID).0;
+        oracle.committee_info = leak_info(global, System::id(), Vec::new(), false);
+        let err = oracle.dispute(&proposal, oracle.traders[0]).err();
+        assert_eq!(err, Some(LikeliError::NoCommittee.into()));
+
+        // Nor does another committee stand in for the market's
+        oracle.committee_info = program_info(&Committee { members: vec![oracle.traders[0].key()], threshold: 1, bump: 0 });
+        let err = oracle.dispute(&proposal, oracle.traders[0]).err();
+        assert_eq!(err, Some(LikeliError::CommitteeMismatch.into()));
+        assert_eq!(oracle.balances(), [950, 1000, 1000, 50]);
+    }
+
+    #[test]
+    fn price_feeds_must_be_owned_by_the_configured_oracle_program() {
+        let oracle_program = Pubkey::new_unique();
+        let market = Market {
+            price_feed: Pubkey::new_unique(),
+            price_feed_owner: oracle_program,
+            strike_price: 100,
+            max_staleness: 60,
+            ..binary_market(Pubkey::default(), 1_000)
+        };
+        let resolve_with = |owner: Pubkey| -> Result<Market> {
+            let feed = leak_info(market.price_feed, owner, price_account(150, 0, 1_010, PRICE_STATUS_TRADING), false);
+            let infos = leak_slice(&[program_info(&market), feed]);
+            let mut accounts = ResolveFromPriceFeed::try_accounts(
+                &crate::ID,
+                &mut &infos[..],
+                &[],
+                &mut Default::default(),
+                &mut Default::default(),
+            )?;
+            likeli_contracts::resolve_from_price_feed(Context::new(&crate::ID, &mut accounts, &[], Default::default()))?;
+            Ok(accounts.market.into_inner())
+        };
+
+        set_now(1_010);
+        // A look-alike account from any other program is refused
+        assert_eq!(resolve_with(Pubkey::new_unique()).err(), Some(LikeliError::InvalidPriceFeed.into()));
+        assert_eq!(resolve_with(oracle_program).unwrap().outcome, Some(MarketOutcome::Yes));
+    }
+
+    fn cancel_abandoned(
+        market: &'static AccountInfo<'static>,
+        state: &MultiMarket,
+        answers: &[&'static AccountInfo<'static>],
+    ) -> Result<MultiMarket> {
+        state.try_serialize(&mut &mut market.try_borrow_mut_data()?[..])?;
+        let mut accounts = CancelAbandonedMultiMarket { market: Account::try_from(market)? };
+        likeli_contracts::cancel_abandoned_multi_market(
+            Context::new(&crate::ID, &mut accounts, leak_slice(answers), Default::default()),
+        )?;
+        Ok(accounts.market.into_inner())
+    }
+
+    #[test]
+    fn abandoned_one_winner_markets_cancel_every_answer() {
+        for is_one_winner in [true, false] {
+            let resolver = signer_info();
+            let market = MultiMarket { is_one_winner, resolution_grace: 100, ..one_winner_market(resolver.key(), 3) };
+            let market_info = program_info(&market);
+            let answers = answer_infos(market_info.key(), 3);
+            set_now(0);
+            let market = resolve(market_info, answers[0], resolver, &[], MarketOutcome::No).unwrap();
+
+            set_now(99);
+            assert_eq!(cancel_abandoned(market_info, &market, &answers).err(), Some(LikeliError::GracePeriodActive.into()));
+            set_now(100);
+            let cancelled = cancel_abandoned(market_info, &market, &answers[1..]);
+            if is_one_winner {
+                // The NO already recorded can't stand alone next to N/A siblings
+                assert_eq!(cancelled.err(), Some(LikeliError::MissingSiblings.into()));
+                let cancelled = cancel_abandoned(market_info, &market, &answers).unwrap();
+                assert_eq!((cancelled.answers_resolved, cancelled.invalid_mask, cancelled.yes_mask), (3, 0b111, 0));
+                assert_eq!(load::<Answer>(answers[0]).outcome, Some(MarketOutcome::Invalid));
+                assert!(cancelled.abandoned && cancelled.resolved);
+            } else {
+                let cancelled = cancelled.unwrap();
+                assert_eq!((cancelled.answers_resolved, cancelled.invalid_mask), (3, 0b110));
+                assert_eq!(cancelled.answer_outcome(0), Some(MarketOutcome::No));
+            }
+        }
+    }
+
+    /// Liquidity provider accounts of a multi-choice market with a funded vault
+    struct AnswerPools {
+        market: &'static AccountInfo<'static>,
+        answers: Vec<&'static AccountInfo<'static>>,
+        lp_positions: Vec<&'static AccountInfo<'static>>,
+        position: &'static AccountInfo<'static>,
+        provider: &'static AccountInfo<'static>,
+        provider_ata: &'static AccountInfo<'static>,
+        vault_ata: &'static AccountInfo<'static>,
+    }
+
+    impl AnswerPools {
+        /// Seed every answer as `add_answer` would, with `seed` collateral from one provider
+        fn new(market: MultiMarket, seed: u64) -> Self {
+            let provider = signer_info();
+            let mint = Pubkey::new_unique();
+            let market_info = program_info(&market);
+            let answers = (0..market.answer_count)
+                .map(|index| program_info(&Answer {
+                    market: market_info.key(),
+                    index,
+                    yes_pool: seed,
+                    no_pool: seed / (market.answer_count as u64 - 1),
+                    total_lp_shares: seed,
+                    ..zeroed()
+                }))
+                .collect::<Vec<_>>();
+            let lp_positions = answers
+                .iter()
+                .map(|answer| program_info(&LpPosition { owner: provider.key(), market: answer.key(), shares: seed, bump: 0 }))
+                .collect();
+            let mut market = market;
+            for index in 0..market.answer_count {
+                market.deposit_collateral(index, seed);
+            }
+            market.try_serialize(&mut &mut market_info.try_borrow_mut_data().unwrap()[..]).unwrap();
+            AnswerPools {
+                market: market_info,
+                answers,
+                lp_positions,
+                position: program_info(&MultiPosition { owner: provider.key(), market: market_info.key(), ..zeroed() }),
+                provider,
+                provider_ata: token_info(mint, provider.key(), 0),
+                vault_ata: token_info(mint, Pubkey::default(), seed * market.answer_count as u64),
+            }
+        }
+
+        fn update<T: AccountSerialize + AccountDeserialize>(info: &AccountInfo, f: impl FnOnce(&mut T)) {
+            let mut value: T = load(info);
+            f(&mut value);
+            value.try_serialize(&mut &mut info.try_borrow_mut_data().unwrap()[..]).unwrap();
+        }
+
+        /// A trader's CPMM buy of `amount`, synced across the siblings like `buy_multi`
+        fn buy(&self, index: usize, amount: u64, is_yes: bool) -> u64 {
+            let answer: Answer = load(self.answers[index]);
+            let (shares, yes_pool, no_pool) = cpmm_buy(answer.yes_pool, answer.no_pool, amount, is_yes);
+            Self::update(self.answers[index], |answer: &mut Answer| (answer.yes_pool, answer.no_pool) = (yes_pool, no_pool));
+            Self::update(self.market, |market: &mut MultiMarket| market.deposit_collateral(index as u8, amount));
+            set_token_balance(self.vault_ata, token_balance(self.vault_ata) + amount);
+
+            let total = (yes_pool + no_pool) as u128;
+            let new_price = if is_yes { no_pool as u128 * 10000 / total } else { yes_pool as u128 * 10000 / total };
+            let siblings = leak_slice(&self.answers);
+            let market = load::<MultiMarket>(self.market);
+            sync_sibling_pools(self.answers[index].key(), new_price as u64, self.market.key(), market.answer_count - 1, siblings).unwrap();
+            shares
+        }
+
+        fn remove_liquidity(&self, index: usize) -> Result<u64> {
+            let before = token_balance(self.provider_ata);
+            let lp_shares = load::<LpPosition>(self.lp_positions[index]).shares;
+            let mut accounts = AnswerLiquidity {
+                market: Account::try_from(self.market)?,
+                answer: Account::try_from(self.answers[index])?,
+                lp_position: Account::try_from(self.lp_positions[index])?,
+                position: Account::try_from(self.position)?,
+                vault_authority: UncheckedAccount::try_from(signer_info()),
+                vault_ata: Account::try_from(self.vault_ata)?,
+                provider_ata: Account::try_from(self.provider_ata)?,
+                provider: Signer::try_from(self.provider)?,
+                system_program: Program::try_from(program_id_info(System::id()))?,
+                token_program: Program::try_from(program_id_info(token::ID))?,
+            };
+            likeli_contracts::remove_answer_liquidity(
+                Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+                lp_shares,
+            )?;
+            accounts.market.exit(&crate::ID)?;
+            accounts.answer.exit(&crate::ID)?;
+            accounts.lp_position.exit(&crate::ID)?;
+            accounts.position.exit(&crate::ID)?;
+            Ok(token_balance(self.provider_ata) - before)
+        }
+    }
+
+    #[test]
+    fn synced_one_winner_pools_pay_out_within_the_vault() {
+        for winner in 0..2 {
+            set_now(0);
+            let resolver = signer_info();
+            let pools = AnswerPools::new(one_winner_market(resolver.key(), 2), 100);
+
+            // Buying answer 0 YES reprices answer 1 without adding collateral to it
+            let bought = pools.buy(0, 10, true);
+            assert_eq!(bought, 19);
+            let synced: Answer = load(pools.answers[1]);
+            assert_eq!((synced.yes_pool, synced.no_pool), (110, 90));
+            assert_eq!(pools.remove_liquidity(0).err(), Some(LikeliError::LiquidityLocked.into()));
+
+            let market = resolve(pools.market, pools.answers[winner], resolver, leak_slice(&pools.answers), MarketOutcome::Yes).unwrap();
+            market.try_serialize(&mut &mut pools.market.try_borrow_mut_data().unwrap()[..]).unwrap();
+
+            let lp_paid = pools.remove_liquidity(0).unwrap() + pools.remove_liquidity(1).unwrap();
+            let trader_paid = if winner == 0 { bought } else { 0 };
+            assert!(lp_paid + trader_paid <= 210, "winner {winner}: {lp_paid} + {trader_paid}");
+            // Uncapped, answer 1's synced YES pool alone would take 110 of its 100
+            assert_eq!(lp_paid, if winner == 0 { 91 + 90 } else { 110 + 100 });
+            let position: MultiPosition = load(pools.position);
+            assert_eq!((position.yes_shares, position.no_shares), ([0; 10], [0; 10]));
+        }
+    }
+
+    #[test]
+    fn answer_seeds_withdraw_after_resolution_under_lmsr() {
+        set_now(0);
+        let resolver = signer_info();
+        let pools = AnswerPools::new(MultiMarket { resolver: resolver.key(), ..lmsr_market(3, 1_000) }, 100);
+        assert_eq!(pools.remove_liquidity(1).err(), Some(LikeliError::WrongPricingEngine.into()));
+
+        let market = resolve(pools.market, pools.answers[1], resolver, leak_slice(&pools.answers), MarketOutcome::Yes).unwrap();
+        market.try_serialize(&mut &mut pools.market.try_borrow_mut_data().unwrap()[..]).unwrap();
+
+        // The winner's YES pool and the losers' NO pools, as seeded
+        let paid: Vec<u64> = (0..3).map(|index| pools.remove_liquidity(index).unwrap()).collect();
+        assert_eq!(paid, [50, 100, 50]);
+        // What's left backs the NO shares each seed minted beyond its pool, 50 per losing answer
+        assert_eq!(token_balance(pools.vault_ata), 100);
+    }
 }